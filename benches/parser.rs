@@ -0,0 +1,103 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use libsbf::parser::SbfParser;
+
+const MEGA_TEST: &[u8] = include_bytes!("../test-files/mega_test.sbf");
+
+/// Split `data` into its `$@` framed blocks by their length fields, without
+/// checking CRCs.
+fn blocks(data: &[u8]) -> Vec<&[u8]> {
+    let mut blocks = Vec::new();
+    let mut i = 0;
+    while i + 8 <= data.len() {
+        let length = usize::from(u16::from_le_bytes([data[i + 6], data[i + 7]]));
+        if &data[i..i + 2] != b"$@" || length < 8 || i + length > data.len() {
+            i += 1;
+            continue;
+        }
+        blocks.push(&data[i..i + length]);
+        i += length;
+    }
+    blocks
+}
+
+/// The mega file with garbage between blocks and every third block's CRC
+/// corrupted, as seen on a noisy serial link.
+fn noisy_mega_test() -> Vec<u8> {
+    let mut state = 0x2545_f491_u32;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state
+    };
+
+    let mut out = Vec::new();
+    for (i, block) in blocks(MEGA_TEST).into_iter().enumerate() {
+        for _ in 0..next() % 64 {
+            out.push(next() as u8);
+        }
+        let start = out.len();
+        out.extend_from_slice(block);
+        if i % 3 == 0 {
+            out[start + 2] ^= 0xFF;
+        }
+    }
+    out
+}
+
+fn drain(parser: &mut SbfParser, input: &[u8]) -> usize {
+    let mut count = 0;
+    if parser.consume(input).is_some() {
+        count += 1;
+        while parser.consume(&[]).is_some() {
+            count += 1;
+        }
+    }
+    count
+}
+
+fn stream_chunks(data: &[u8], chunk: usize) -> usize {
+    let mut parser = SbfParser::new();
+    data.chunks(chunk).map(|c| drain(&mut parser, c)).sum()
+}
+
+fn bench_parser(c: &mut Criterion) {
+    let noisy = noisy_mega_test();
+    let mut large = Vec::new();
+    for _ in 0..8 {
+        large.extend_from_slice(&noisy);
+    }
+
+    let mut group = c.benchmark_group("sbf_parser");
+
+    group.throughput(Throughput::Bytes(MEGA_TEST.len() as u64));
+    group.bench_function("mega_test_4k_chunks", |b| {
+        b.iter(|| stream_chunks(MEGA_TEST, 4096))
+    });
+    group.bench_function("mega_test_whole", |b| {
+        b.iter_batched(
+            SbfParser::new,
+            |mut p| drain(&mut p, MEGA_TEST),
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.throughput(Throughput::Bytes(noisy.len() as u64));
+    group.bench_function("noisy_4k_chunks", |b| {
+        b.iter(|| stream_chunks(&noisy, 4096))
+    });
+
+    group.throughput(Throughput::Bytes(large.len() as u64));
+    group.bench_function("noisy_large_whole", |b| {
+        b.iter_batched(
+            SbfParser::new,
+            |mut p| drain(&mut p, &large),
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_parser);
+criterion_main!(benches);
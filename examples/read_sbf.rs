@@ -50,9 +50,7 @@ fn main() -> anyhow::Result<()> {
         if args.verbose {
             println!("{msg:?}");
         }
-        let debug = format!("{msg:?}");
-        let msg_type = debug.split('(').next().unwrap().to_string();
-        *stats.entry(msg_type).or_insert(0) += 1;
+        *stats.entry(msg.type_name().to_string()).or_insert(0) += 1;
     }
 
     eprintln!("\n=== Message Statistics ===");
@@ -1,4 +1,4 @@
-use libsbf::parse_datagram;
+use libsbf::parse_datagrams;
 
 use clap::Parser;
 use std::collections::HashMap;
@@ -21,43 +21,6 @@ struct Args {
     verbose: bool,
 }
 
-/// Parse all SBF blocks from a buffer. The device may pack multiple blocks
-/// into one UDP datagram.
-fn parse_all_blocks(data: &[u8]) -> Vec<Result<libsbf::Messages, libsbf::DatagramError>> {
-    let mut results = Vec::new();
-    let mut offset = 0;
-
-    while offset < data.len() {
-        let remaining = &data[offset..];
-        let sync_pos = remaining.windows(2).position(|w| w == b"$@");
-
-        let start = match sync_pos {
-            Some(pos) => offset + pos,
-            None => break,
-        };
-
-        match parse_datagram(&data[start..]) {
-            Ok(msg) => {
-                // Read block length from header to advance past this block
-                if data.len() >= start + 8 {
-                    let length =
-                        u16::from_le_bytes([data[start + 6], data[start + 7]]) as usize;
-                    offset = start + length;
-                } else {
-                    offset = data.len();
-                }
-                results.push(Ok(msg));
-            }
-            Err(e) => {
-                results.push(Err(e));
-                offset = start + 2;
-            }
-        }
-    }
-
-    results
-}
-
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
 
@@ -74,7 +37,8 @@ fn main() -> anyhow::Result<()> {
         let n = socket.recv(&mut buf)?;
         datagrams += 1;
 
-        let blocks = parse_all_blocks(&buf[..n]);
+        // The device may pack multiple blocks into one UDP datagram.
+        let blocks: Vec<_> = parse_datagrams(&buf[..n]).collect();
         if blocks.len() > 1 {
             multi_block += 1;
         }
@@ -85,9 +49,7 @@ fn main() -> anyhow::Result<()> {
                     if args.verbose {
                         println!("{msg:?}");
                     }
-                    let debug = format!("{msg:?}");
-                    let msg_type = debug.split('(').next().unwrap().to_string();
-                    *stats.entry(msg_type).or_insert(0) += 1;
+                    *stats.entry(msg.type_name().to_string()).or_insert(0) += 1;
                 }
                 Err(e) => {
                     *stats.entry(format!("Error({e:?})")).or_insert(0) += 1;
@@ -40,7 +40,9 @@
           nativeBuildInputs = [
             cargo-afl
             llvmPackages.libllvm
-            rust-bin.stable.latest.default
+            (rust-bin.stable.latest.default.override {
+              targets = [ "thumbv7em-none-eabihf" ];
+            })
             valgrind
           ];
         };
@@ -1,6 +1,7 @@
-use crate::{Datum, SubBlock};
+use crate::{Datum, NestedBlock, NestedHeader, SubBlock};
 use alloc::vec::Vec;
-use binrw::{BinRead, BinWrite};
+use binrw::io::{Read, Seek, Write};
+use binrw::{BinRead, BinResult, BinWrite, Endian};
 
 // Constants for DO_NOT_USE values
 const DO_NOT_USE_I1: i8 = -128;
@@ -111,22 +112,99 @@ pub(crate) fn unmap_datum(x: &Option<Datum>) -> u8 {
     x.map_or(DO_NOT_USE_U1, u8::from)
 }
 
-// Read `count` sub-blocks, each padded to its SBLength, unwrapped to the bare type.
-pub(crate) fn unwrap_subblocks<T>(v: Vec<SubBlock<T>>) -> Vec<T>
+// Read the rest of the body in one go, where `until_eof` would read it a byte
+// at a time.
+#[binrw::parser(reader)]
+pub(crate) fn read_to_end() -> binrw::BinResult<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+// Write `item`, then zero padding up to `len` bytes from where it started.
+fn write_padded<W, T>(item: &T, writer: &mut W, endian: Endian, len: usize) -> BinResult<()>
 where
-    T: 'static,
-    for<'a> T: BinRead<Args<'a> = ()> + BinWrite<Args<'a> = ()>,
+    W: Write + Seek,
+    for<'a> T: BinWrite<Args<'a> = ()>,
 {
-    v.into_iter().map(SubBlock::into_inner).collect()
+    let end = writer.stream_position()? + len as u64;
+    item.write_options(writer, endian, ())?;
+    for _ in writer.stream_position()?..end {
+        0u8.write_options(writer, endian, ())?;
+    }
+    Ok(())
 }
 
-// Wrap the bare sub-blocks so each writes back padded to its SBLength. binrw's
-// bw map passes the field as `&Vec<T>`, so the signature has to match it exactly.
-#[allow(clippy::ptr_arg)]
-pub(crate) fn wrap_subblocks<T>(v: &Vec<T>) -> Vec<SubBlock<T>>
+// Read `count` sub-blocks, each padded to its SBLength, unwrapped to the bare type.
+pub(crate) fn read_subblocks<R, T>(
+    reader: &mut R,
+    endian: Endian,
+    (count, sb_len): (usize, usize),
+) -> BinResult<Vec<T>>
 where
-    T: 'static + Clone,
+    R: Read + Seek,
+    T: 'static,
     for<'a> T: BinRead<Args<'a> = ()> + BinWrite<Args<'a> = ()>,
 {
-    v.iter().cloned().map(SubBlock::from).collect()
+    (0..count)
+        .map(|_| Ok(SubBlock::<T>::read_options(reader, endian, (sb_len,))?.into_inner()))
+        .collect()
+}
+
+// Write the bare sub-blocks back, each padded to its SBLength.
+pub(crate) fn write_subblocks<W, T, L>(
+    items: &L,
+    writer: &mut W,
+    endian: Endian,
+    (sb_len,): (usize,),
+) -> BinResult<()>
+where
+    W: Write + Seek,
+    for<'a> T: BinWrite<Args<'a> = ()>,
+    L: AsRef<[T]> + ?Sized,
+{
+    for item in items.as_ref() {
+        write_padded(item, writer, endian, sb_len)?;
+    }
+    Ok(())
+}
+
+// Read `count` first-level sub-blocks of a two-level block, each a header padded
+// to SB1Length followed by its nested sub-blocks padded to SB2Length.
+pub(crate) fn read_nested_blocks<R, B>(
+    reader: &mut R,
+    endian: Endian,
+    (count, sb1_len, sb2_len): (usize, usize, usize),
+) -> BinResult<Vec<B>>
+where
+    R: Read + Seek,
+    B: NestedBlock,
+{
+    (0..count)
+        .map(|_| {
+            let header =
+                SubBlock::<B::Header>::read_options(reader, endian, (sb1_len,))?.into_inner();
+            let items = read_subblocks(reader, endian, (header.nested_count(), sb2_len))?;
+            Ok(B::from_parts(header, items))
+        })
+        .collect()
+}
+
+// Write first-level sub-blocks back, mirroring `read_nested_blocks`.
+pub(crate) fn write_nested_blocks<W, B, L>(
+    blocks: &L,
+    writer: &mut W,
+    endian: Endian,
+    (sb1_len, sb2_len): (usize, usize),
+) -> BinResult<()>
+where
+    W: Write + Seek,
+    B: NestedBlock,
+    L: AsRef<[B]> + ?Sized,
+{
+    for block in blocks.as_ref() {
+        write_padded(&block.header(), writer, endian, sb1_len)?;
+        write_subblocks(block.items(), writer, endian, (sb2_len,))?;
+    }
+    Ok(())
 }
@@ -0,0 +1,168 @@
+//! Async support for tokio: a [`tokio_util::codec`] codec for SBF streams and
+//! a UDP helper around the datagram parser.
+
+use crate::parser::{parse_datagrams, BlockFilter, Datagrams, ParserStats, SbfParser};
+use crate::Messages;
+
+use bytes::{Buf, BytesMut};
+use std::io;
+use tokio::net::UdpSocket;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Decodes a byte stream into [`Messages`] and encodes messages back into
+/// framed blocks, for use with [`tokio_util::codec::Framed`] over TCP or a
+/// serial port.
+///
+/// # Examples
+///
+/// ```no_run
+/// use futures_util::StreamExt;
+/// use libsbf::codec::SbfCodec;
+/// use tokio::net::TcpStream;
+/// use tokio_util::codec::FramedRead;
+///
+/// # async fn run() -> std::io::Result<()> {
+/// let stream = TcpStream::connect("127.0.0.1:8080").await?;
+/// let mut messages = FramedRead::new(stream, SbfCodec::new());
+/// while let Some(m) = messages.next().await {
+///     eprintln!("{:?}", m?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[derive(Default)]
+pub struct SbfCodec {
+    parser: SbfParser,
+}
+
+impl SbfCodec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only decode the blocks `filter` selects.
+    pub fn with_filter(mut self, filter: BlockFilter) -> Self {
+        self.parser = self.parser.with_filter(filter);
+        self
+    }
+
+    /// Counters for everything the underlying parser has consumed so far.
+    pub fn stats(&self) -> ParserStats {
+        self.parser.stats()
+    }
+}
+
+impl Decoder for SbfCodec {
+    type Item = Messages;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Messages>, io::Error> {
+        // The parser keeps its own buffer, so hand it everything read so far.
+        let msg = self.parser.consume(src);
+        src.advance(src.len());
+        Ok(msg)
+    }
+}
+
+impl Encoder<Messages> for SbfCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: Messages, dst: &mut BytesMut) -> Result<(), io::Error> {
+        self.encode(&item, dst)
+    }
+}
+
+impl Encoder<&Messages> for SbfCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: &Messages, dst: &mut BytesMut) -> Result<(), io::Error> {
+        let block = item
+            .encode()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        dst.extend_from_slice(&block);
+        Ok(())
+    }
+}
+
+/// Receive one datagram from `socket` into `buf` and parse every block in it,
+/// as [`parse_datagrams`] does.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio::net::UdpSocket;
+///
+/// # async fn run() -> std::io::Result<()> {
+/// let socket = UdpSocket::bind("0.0.0.0:28785").await?;
+/// let mut buf = [0u8; 65535];
+/// loop {
+///     for m in libsbf::codec::recv_datagrams(&socket, &mut buf).await? {
+///         println!("{:?}", m);
+///     }
+/// }
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub async fn recv_datagrams<'a>(
+    socket: &UdpSocket,
+    buf: &'a mut [u8],
+) -> io::Result<Datagrams<'a>> {
+    let len = socket.recv(buf).await?;
+    Ok(parse_datagrams(&buf[..len]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{recv_datagrams, SbfCodec};
+    use crate::parser::parse_datagram;
+    use crate::test_fixtures::VALID_QUALITY_IND;
+    use crate::Messages;
+
+    use bytes::BytesMut;
+    use tokio::net::UdpSocket;
+    use tokio_util::codec::{Decoder, Encoder};
+
+    #[test]
+    fn test_codec_round_trip() {
+        let msg = parse_datagram(VALID_QUALITY_IND).unwrap();
+        let mut codec = SbfCodec::new();
+        let mut stream = BytesMut::new();
+        codec.encode(&msg, &mut stream).unwrap();
+        codec.encode(msg, &mut stream).unwrap();
+        assert_eq!(&stream[..32], &VALID_QUALITY_IND[..]);
+
+        // Split mid-block, as a TCP read might.
+        let mut first = stream.split_to(40);
+        assert!(matches!(
+            codec.decode(&mut first),
+            Ok(Some(Messages::QualityInd(_)))
+        ));
+        assert!(matches!(codec.decode(&mut first), Ok(None)));
+        assert!(first.is_empty());
+        assert!(matches!(
+            codec.decode(&mut stream),
+            Ok(Some(Messages::QualityInd(_)))
+        ));
+        assert_eq!(codec.stats().messages, 2);
+    }
+
+    #[tokio::test]
+    async fn test_recv_datagrams() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sender = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut packet = VALID_QUALITY_IND.to_vec();
+        packet.extend_from_slice(VALID_QUALITY_IND);
+        sender
+            .send_to(&packet, receiver.local_addr().unwrap())
+            .await
+            .unwrap();
+
+        let mut buf = [0u8; 1024];
+        let blocks: Vec<_> = recv_datagrams(&receiver, &mut buf).await.unwrap().collect();
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks
+            .iter()
+            .all(|b| matches!(b, Ok(Messages::QualityInd(_)))));
+    }
+}
@@ -0,0 +1,423 @@
+//! Demultiplex a receiver port that carries SBF interleaved with NMEA
+//! sentences, RTCM 3 frames and replies to ASCII commands.
+//!
+//! [`Demuxer`] is sans-io like [`crate::parser::SbfParser`]: feed it bytes as
+//! they arrive and it returns each complete frame it recognizes. Bytes that
+//! belong to none of them are skipped.
+//!
+//! ```
+//! use libsbf::demux::{Demuxed, Demuxer};
+//!
+//! let mut demux = Demuxer::new();
+//! let mut input: &[u8] = b"$GPGGA,092750.000,5321.6802,N,00630.3372,W,1,8,1.03,61.7,M,55.2,M,,*76\r\n";
+//! while let Some(frame) = demux.consume(input) {
+//!     input = &[];
+//!     match frame {
+//!         Demuxed::Sbf(msg) => println!("{msg:?}"),
+//!         Demuxed::Nmea(sentence) => println!("{sentence}"),
+//!         Demuxed::Rtcm3 { msg_type, bytes } => println!("RTCM {msg_type}: {} bytes", bytes.len()),
+//!         Demuxed::CommandReply(reply) => println!("{reply}"),
+//!     }
+//! }
+//! ```
+
+use crate::parser::{check_frame, decode_body, ParseError};
+use crate::Messages;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// First byte of every RTCM 3 frame.
+const RTCM3_PREAMBLE: u8 = 0xD3;
+
+/// Longest sentence accepted. NMEA 0183 allows 82 characters, but
+/// proprietary sentences run longer.
+const MAX_NMEA_LEN: usize = 1024;
+
+/// Longest command reply accepted, enough for a full configuration listing.
+const MAX_REPLY_LEN: usize = 64 * 1024;
+
+/// A frame recognized by [`Demuxer`]. Borrowed data is valid until the next
+/// call to [`Demuxer::consume`].
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum Demuxed<'a> {
+    /// A CRC-checked SBF block.
+    Sbf(Messages),
+    /// An NMEA sentence from `$` or `!` up to its checksum, without the line
+    /// ending. The checksum, if present, has been verified.
+    Nmea(&'a str),
+    /// A CRC-24Q checked RTCM 3 frame: preamble, length, message and CRC.
+    /// `msg_type` is the 12-bit message number, 0 for an empty message.
+    Rtcm3 { msg_type: u16, bytes: &'a [u8] },
+    /// A reply to an ASCII command (`$R:`, `$R;`, `$R?`) or a transmission
+    /// error (`$TE`), up to and including the prompt that ends it, e.g.
+    /// `COM1>`.
+    CommandReply(String),
+}
+
+/// What the data at a candidate start byte turned out to be.
+#[allow(clippy::large_enum_variant)]
+enum Scan {
+    /// A complete frame of `len` bytes.
+    Frame(Kind, usize),
+    /// Could still be a frame once more data arrives. Its end is not in
+    /// the first `scanned` bytes, so the next scan can resume there.
+    NeedMore { scanned: usize },
+    /// Not a frame; skip the start byte.
+    Invalid,
+    /// A CRC-valid SBF block whose body did not decode; skip it whole.
+    BadSbf(usize),
+}
+
+#[allow(clippy::large_enum_variant)]
+enum Kind {
+    Sbf(Messages),
+    Nmea,
+    Rtcm3,
+    CommandReply,
+}
+
+/// Streaming demultiplexer for mixed SBF, NMEA, RTCM 3 and command reply
+/// traffic. See the [module docs](self).
+#[derive(Debug, Default)]
+pub struct Demuxer {
+    buf: Vec<u8>,
+    /// Read cursor into `buf`.
+    start: usize,
+    /// Size of the last returned frame, still at the front of the unread
+    /// data until the next call.
+    frame_len: usize,
+    /// Bytes of the pending frame at `start` already scanned for its end.
+    scanned: usize,
+    bytes_skipped: u64,
+}
+
+impl Demuxer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bytes discarded so far because they were not part of any recognized
+    /// frame.
+    pub fn bytes_skipped(&self) -> u64 {
+        self.bytes_skipped
+    }
+
+    /// Consume bytes and return the next complete frame, or None when more
+    /// data is needed. Like [`crate::parser::SbfParser::consume`], call again
+    /// with an empty slice to drain frames still buffered.
+    pub fn consume(&mut self, input: &[u8]) -> Option<Demuxed<'_>> {
+        self.start += core::mem::take(&mut self.frame_len);
+        if self.start > 0 && self.start >= self.buf.len() - self.start {
+            self.buf.drain(..self.start);
+            self.start = 0;
+        }
+        self.buf.extend_from_slice(input);
+
+        let (kind, len) = loop {
+            let unread = &self.buf[self.start..];
+            let Some(i) = memchr::memchr3(b'$', b'!', RTCM3_PREAMBLE, unread) else {
+                self.skip(unread.len());
+                return None;
+            };
+            self.skip(i);
+
+            match scan(&self.buf[self.start..], self.scanned) {
+                Scan::Frame(kind, len) => break (kind, len),
+                Scan::NeedMore { scanned } => {
+                    self.scanned = scanned;
+                    return None;
+                }
+                Scan::Invalid => self.skip(1),
+                Scan::BadSbf(len) => self.skip(len),
+            }
+        };
+
+        self.frame_len = len;
+        self.scanned = 0;
+        let frame = &self.buf[self.start..self.start + len];
+        Some(match kind {
+            Kind::Sbf(msg) => Demuxed::Sbf(msg),
+            // Checked to be printable ASCII.
+            Kind::Nmea => {
+                Demuxed::Nmea(core::str::from_utf8(trim_line_end(frame)).unwrap_or_default())
+            }
+            Kind::Rtcm3 => {
+                let msg_type = if frame.len() >= 8 {
+                    (u16::from(frame[3]) << 4) | (u16::from(frame[4]) >> 4)
+                } else {
+                    0
+                };
+                Demuxed::Rtcm3 {
+                    msg_type,
+                    bytes: frame,
+                }
+            }
+            Kind::CommandReply => {
+                Demuxed::CommandReply(String::from_utf8_lossy(frame).into_owned())
+            }
+        })
+    }
+
+    fn skip(&mut self, n: usize) {
+        if n > 0 {
+            self.start += n;
+            self.scanned = 0;
+            self.bytes_skipped += n as u64;
+        }
+    }
+}
+
+/// Work out what the frame starting at `input[0]` is, given that its end is
+/// not in the first `scanned` bytes.
+fn scan(input: &[u8], scanned: usize) -> Scan {
+    match input {
+        [b'$', b'@', ..] => match check_frame(input, usize::from(u16::MAX)) {
+            Ok((block_id, frame)) => match decode_body(&block_id, &frame[8..]) {
+                Ok(msg) => Scan::Frame(Kind::Sbf(msg), frame.len()),
+                Err(_) => Scan::BadSbf(frame.len()),
+            },
+            Err(ParseError::IncompleteData) => Scan::NeedMore { scanned: 0 },
+            Err(_) => Scan::Invalid,
+        },
+        [b'$', b'R', b':' | b';' | b'?', ..] | [b'$', b'T', b'E', ..] => scan_reply(input, scanned),
+        [b'$' | b'!', ..] => scan_nmea(input, scanned),
+        [RTCM3_PREAMBLE, ..] => scan_rtcm3(input),
+        _ => Scan::Invalid,
+    }
+}
+
+/// An NMEA sentence: printable ASCII ended by a line break, with a valid
+/// checksum if it has one.
+fn scan_nmea(input: &[u8], scanned: usize) -> Scan {
+    let limit = input.len().min(MAX_NMEA_LEN);
+    let from = scanned.max(1);
+    let Some(end) = input[from..limit]
+        .iter()
+        .position(|&b| !(0x20..=0x7E).contains(&b))
+    else {
+        return if limit < MAX_NMEA_LEN {
+            Scan::NeedMore { scanned: limit }
+        } else {
+            Scan::Invalid
+        };
+    };
+    let end = from + end;
+    let len = match &input[end..] {
+        [b'\r', b'\n', ..] => end + 2,
+        [b'\n', ..] => end + 1,
+        [b'\r'] => return Scan::NeedMore { scanned: end },
+        _ => return Scan::Invalid,
+    };
+
+    let sentence = &input[..end];
+    if sentence.len() < 6 || !sentence[1..3].iter().all(u8::is_ascii_alphanumeric) {
+        return Scan::Invalid;
+    }
+    match sentence {
+        [body @ .., b'*', hi, lo] => {
+            let checksum = body[1..].iter().fold(0, |sum, b| sum ^ b);
+            match (hex_digit(*hi), hex_digit(*lo)) {
+                (Some(hi), Some(lo)) if (hi << 4 | lo) == checksum => Scan::Frame(Kind::Nmea, len),
+                _ => Scan::Invalid,
+            }
+        }
+        _ if sentence.contains(&b'*') => Scan::Invalid,
+        _ => Scan::Frame(Kind::Nmea, len),
+    }
+}
+
+/// A command reply, which runs until a prompt such as `COM1>` at the start
+/// of a line.
+fn scan_reply(input: &[u8], scanned: usize) -> Scan {
+    let limit = input.len().min(MAX_REPLY_LEN);
+    let more = |scanned| {
+        if limit < MAX_REPLY_LEN {
+            Scan::NeedMore { scanned }
+        } else {
+            Scan::Invalid
+        }
+    };
+    for line_end in memchr::memchr_iter(b'\n', &input[scanned..limit]) {
+        let line_end = scanned + line_end;
+        if input[line_end - 1] != b'\r' {
+            continue;
+        }
+        let prompt = &input[line_end + 1..limit];
+        let name = prompt
+            .iter()
+            .take_while(|b| b.is_ascii_alphanumeric())
+            .count();
+        match prompt.get(name) {
+            Some(b'>') if name > 0 => return Scan::Frame(Kind::CommandReply, line_end + name + 2),
+            // The prompt may still be arriving.
+            None => return more(line_end),
+            _ => {}
+        }
+    }
+    more(limit)
+}
+
+/// An RTCM 3 frame: preamble, 6 reserved zero bits, a 10-bit message length,
+/// the message and a CRC-24Q over everything before it.
+fn scan_rtcm3(input: &[u8]) -> Scan {
+    let [_, hi, lo, ..] = *input else {
+        return Scan::NeedMore { scanned: 0 };
+    };
+    if hi & 0xFC != 0 {
+        return Scan::Invalid;
+    }
+    let len = 3 + (usize::from(hi) << 8 | usize::from(lo)) + 3;
+    if input.len() < len {
+        return Scan::NeedMore { scanned: 0 };
+    }
+    let crc = u32::from_be_bytes([0, input[len - 3], input[len - 2], input[len - 1]]);
+    if crc24q(&input[..len - 3]) == crc {
+        Scan::Frame(Kind::Rtcm3, len)
+    } else {
+        Scan::Invalid
+    }
+}
+
+/// CRC-24Q as used by RTCM 3 and SBAS.
+fn crc24q(data: &[u8]) -> u32 {
+    const POLY: u32 = 0x186_4CFB;
+    let mut crc = 0u32;
+    for &b in data {
+        crc ^= u32::from(b) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x100_0000 != 0 {
+                crc ^= POLY;
+            }
+        }
+    }
+    crc & 0xFF_FFFF
+}
+
+fn hex_digit(b: u8) -> Option<u8> {
+    (b as char).to_digit(16).map(|d| d as u8)
+}
+
+fn trim_line_end(frame: &[u8]) -> &[u8] {
+    let frame = frame.strip_suffix(b"\n").unwrap_or(frame);
+    frame.strip_suffix(b"\r").unwrap_or(frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crc24q, Demuxed, Demuxer};
+    use crate::test_fixtures::{long_block, VALID_QUALITY_IND};
+    use crate::Messages;
+
+    use alloc::string::ToString;
+    use alloc::{format, vec, vec::Vec};
+
+    const GGA: &[u8] =
+        b"$GPGGA,092750.000,5321.6802,N,00630.3372,W,1,8,1.03,61.7,M,55.2,M,,*76\r\n";
+
+    /// An RTCM 1005 frame with an all-zero body.
+    fn rtcm1005() -> Vec<u8> {
+        let mut frame = vec![0xD3, 0x00, 19, 0x3E, 0xD0];
+        frame.resize(3 + 19, 0);
+        let crc = crc24q(&frame);
+        frame.extend_from_slice(&crc.to_be_bytes()[1..]);
+        frame
+    }
+
+    #[test]
+    fn test_crc24q() {
+        assert_eq!(crc24q(b"123456789"), 0xCDE703);
+    }
+
+    #[test]
+    fn test_demux_mixed_stream() {
+        let reply = b"$R: gecm\r\n  SetEventParameters, Pos, HighToLow\r\nCOM1>";
+        let mut stream = b"junk".to_vec();
+        stream.extend_from_slice(VALID_QUALITY_IND);
+        stream.extend_from_slice(GGA);
+        stream.extend_from_slice(&rtcm1005());
+        stream.extend_from_slice(reply);
+        // A corrupted sentence is skipped.
+        let mut bad_gga = GGA.to_vec();
+        bad_gga[20] = b'9';
+        stream.extend_from_slice(&bad_gga);
+        stream.extend_from_slice(VALID_QUALITY_IND);
+
+        // Feed it a byte at a time, so every frame arrives in pieces.
+        let mut demux = Demuxer::new();
+        let mut frames = Vec::new();
+        for b in &stream {
+            let mut input = core::slice::from_ref(b);
+            while let Some(frame) = demux.consume(input) {
+                input = &[];
+                frames.push(match frame {
+                    Demuxed::Sbf(Messages::QualityInd(_)) => "sbf".to_string(),
+                    Demuxed::Nmea(sentence) => {
+                        assert_eq!(sentence.as_bytes(), &GGA[..GGA.len() - 2]);
+                        "nmea".to_string()
+                    }
+                    Demuxed::Rtcm3 { msg_type, bytes } => {
+                        assert_eq!(bytes, &rtcm1005()[..]);
+                        format!("rtcm{msg_type}")
+                    }
+                    Demuxed::CommandReply(text) => {
+                        assert_eq!(text.as_bytes(), reply);
+                        "reply".to_string()
+                    }
+                    other => panic!("unexpected frame {other:?}"),
+                });
+            }
+        }
+        assert_eq!(frames, ["sbf", "nmea", "rtcm1005", "reply", "sbf"]);
+        assert_eq!(demux.bytes_skipped(), (4 + GGA.len()) as u64);
+    }
+
+    #[test]
+    fn test_demux_block_longer_than_datagram() {
+        let mut stream = long_block();
+        stream.extend_from_slice(GGA);
+
+        let mut demux = Demuxer::new();
+        match demux.consume(&stream) {
+            Some(Demuxed::Sbf(Messages::RawBlock(raw))) => {
+                assert_eq!(raw.id, 4999);
+                assert_eq!(raw.payload.len(), 65532 - 14);
+            }
+            other => panic!("unexpected frame {other:?}"),
+        }
+        assert!(matches!(demux.consume(&[]), Some(Demuxed::Nmea(_))));
+        assert_eq!(demux.bytes_skipped(), 0);
+    }
+
+    #[test]
+    fn test_demux_long_frames_byte_by_byte() {
+        let mut reply = b"$R: lstConfigFile, Current\r\n".to_vec();
+        for _ in 0..2000 {
+            reply.extend_from_slice(b"  setDataInOut, COM1, CMD, SBF\r\n");
+        }
+        reply.extend_from_slice(b"COM1>");
+        let mut stream = reply.clone();
+        stream.extend_from_slice(&long_block());
+
+        // Each byte only extends the scan of the pending frame, rather than
+        // rescanning it from its start.
+        let mut demux = Demuxer::new();
+        let mut frames = Vec::new();
+        for b in &stream {
+            if let Some(frame) = demux.consume(core::slice::from_ref(b)) {
+                frames.push(match frame {
+                    Demuxed::CommandReply(text) => {
+                        assert_eq!(text.as_bytes(), reply);
+                        "reply"
+                    }
+                    Demuxed::Sbf(Messages::RawBlock(_)) => "sbf",
+                    other => panic!("unexpected frame {other:?}"),
+                });
+            }
+        }
+        assert_eq!(frames, ["reply", "sbf"]);
+        assert_eq!(demux.bytes_skipped(), 0);
+    }
+}
@@ -0,0 +1,343 @@
+//! Fan decoded messages out to handlers registered per block type.
+//!
+//! [`Dispatcher`] boxes closures and trait objects registered at run time,
+//! and drives them from an [`SbfParser`] or, with `std`, an
+//! [`crate::reader::SbfReader`].
+//!
+//! ```
+//! use libsbf::dispatch::Dispatcher;
+//! use libsbf::parser::SbfParser;
+//! use libsbf::{AttEuler, PVTGeodetic};
+//!
+//! # let bytes: &[u8] = &[];
+//! let mut heading = None;
+//! let mut errors = 0;
+//! let mut dispatcher = Dispatcher::new()
+//!     .on::<AttEuler>(|att| heading = att.heading)
+//!     .on::<PVTGeodetic>(|pvt| println!("{:?}", pvt.latitude))
+//!     .on_any(|msg| println!("{}", msg.type_name()))
+//!     .on_error(|_| errors += 1);
+//!
+//! let mut parser = SbfParser::new();
+//! dispatcher.feed(&mut parser, bytes);
+//! ```
+//!
+//! ## Static dispatch
+//! Without boxing, implement [`Handler`] for your own type, or combine the
+//! [`on`], [`on_any`] and [`on_error`] handlers in a tuple, and pass it to
+//! [`dispatch`]. The handlers are then resolved at compile time, which suits
+//! embedded targets that avoid trait objects.
+//!
+//! ```
+//! use libsbf::dispatch::{dispatch, on, on_error};
+//! use libsbf::parser::FixedSbfParser;
+//! use libsbf::{AttEuler, PVTGeodetic};
+//!
+//! # let bytes: &[u8] = &[];
+//! let mut heading = None;
+//! let mut fixes = 0;
+//! let mut errors = 0;
+//! let mut handlers = (
+//!     on::<AttEuler, _>(|att| heading = att.heading),
+//!     on::<PVTGeodetic, _>(|_| fixes += 1),
+//!     on_error(|_| errors += 1),
+//! );
+//!
+//! let mut parser = FixedSbfParser::<4096>::default();
+//! dispatch(&mut parser, bytes, &mut handlers);
+//! ```
+
+use crate::parser::{ParserBuffer, SbfEvent, SbfParser};
+use crate::{Messages, SbfBlock};
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+/// Receives what a parser observes, see [`dispatch`].
+pub trait Handler {
+    /// Called with every decoded message.
+    fn on_message(&mut self, msg: &Messages);
+
+    /// Called with every event that reports a bad block: header, CRC,
+    /// payload and validation errors. Skipped bytes and filtered blocks are
+    /// not errors.
+    fn on_error(&mut self, event: &SbfEvent) {
+        let _ = event;
+    }
+
+    /// Called with every event; sorts them into [`Handler::on_message`] and
+    /// [`Handler::on_error`].
+    fn on_event(&mut self, event: &SbfEvent) {
+        match event {
+            SbfEvent::Message(msg) => self.on_message(msg),
+            SbfEvent::HeaderError { .. }
+            | SbfEvent::CrcError { .. }
+            | SbfEvent::PayloadError { .. }
+            | SbfEvent::ValidationError { .. } => self.on_error(event),
+            SbfEvent::Filtered { .. } | SbfEvent::Skipped { .. } => {}
+        }
+    }
+}
+
+impl<H: Handler + ?Sized> Handler for &mut H {
+    fn on_message(&mut self, msg: &Messages) {
+        (**self).on_message(msg)
+    }
+
+    fn on_error(&mut self, event: &SbfEvent) {
+        (**self).on_error(event)
+    }
+
+    fn on_event(&mut self, event: &SbfEvent) {
+        (**self).on_event(event)
+    }
+}
+
+macro_rules! impl_handler_for_tuple {
+    ($($name:ident),+) => {
+        /// Every handler in the tuple sees every event, in order.
+        impl<$($name: Handler),+> Handler for ($($name,)+) {
+            fn on_message(&mut self, msg: &Messages) {
+                #[allow(non_snake_case)]
+                let ($($name,)+) = self;
+                $( $name.on_message(msg); )+
+            }
+
+            fn on_error(&mut self, event: &SbfEvent) {
+                #[allow(non_snake_case)]
+                let ($($name,)+) = self;
+                $( $name.on_error(event); )+
+            }
+
+            fn on_event(&mut self, event: &SbfEvent) {
+                #[allow(non_snake_case)]
+                let ($($name,)+) = self;
+                $( $name.on_event(event); )+
+            }
+        }
+    };
+}
+
+impl_handler_for_tuple!(A);
+impl_handler_for_tuple!(A, B);
+impl_handler_for_tuple!(A, B, C);
+impl_handler_for_tuple!(A, B, C, D);
+impl_handler_for_tuple!(A, B, C, D, E);
+impl_handler_for_tuple!(A, B, C, D, E, F);
+impl_handler_for_tuple!(A, B, C, D, E, F, G);
+impl_handler_for_tuple!(A, B, C, D, E, F, G, H);
+
+/// Handler calling `f` with every `T` message, created by [`on`].
+pub struct On<T, F> {
+    f: F,
+    block: PhantomData<fn(&T)>,
+}
+
+/// Call `f` with every message that is a `T`.
+pub fn on<T: SbfBlock, F: FnMut(&T)>(f: F) -> On<T, F> {
+    On {
+        f,
+        block: PhantomData,
+    }
+}
+
+impl<T: SbfBlock, F: FnMut(&T)> Handler for On<T, F> {
+    fn on_message(&mut self, msg: &Messages) {
+        if let Some(m) = msg.downcast_ref::<T>() {
+            (self.f)(m)
+        }
+    }
+}
+
+/// Handler calling `f` with every message, created by [`on_any`].
+pub struct OnAny<F> {
+    f: F,
+}
+
+/// Call `f` with every message.
+pub fn on_any<F: FnMut(&Messages)>(f: F) -> OnAny<F> {
+    OnAny { f }
+}
+
+impl<F: FnMut(&Messages)> Handler for OnAny<F> {
+    fn on_message(&mut self, msg: &Messages) {
+        (self.f)(msg)
+    }
+}
+
+/// Handler calling `f` with every error event, created by [`on_error`].
+pub struct OnError<F> {
+    f: F,
+}
+
+/// Call `f` with every error event, see [`Handler::on_error`].
+pub fn on_error<F: FnMut(&SbfEvent)>(f: F) -> OnError<F> {
+    OnError { f }
+}
+
+impl<F: FnMut(&SbfEvent)> Handler for OnError<F> {
+    fn on_message(&mut self, _msg: &Messages) {}
+
+    fn on_error(&mut self, event: &SbfEvent) {
+        (self.f)(event)
+    }
+}
+
+/// Feed `input` to `parser` and hand every event it yields to `handler`,
+/// until the parser needs more data.
+pub fn dispatch<B, H>(parser: &mut SbfParser<B>, mut input: &[u8], handler: &mut H)
+where
+    B: ParserBuffer,
+    H: Handler + ?Sized,
+{
+    while let Some(event) = parser.consume_event(input) {
+        input = &[];
+        handler.on_event(&event);
+    }
+}
+
+/// Handlers registered at run time, called in the order they were added.
+/// See the [module docs](self).
+#[derive(Default)]
+pub struct Dispatcher<'a> {
+    handlers: Vec<Box<dyn Handler + 'a>>,
+}
+
+impl core::fmt::Debug for Dispatcher<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Dispatcher")
+            .field("handlers", &self.handlers.len())
+            .finish()
+    }
+}
+
+impl<'a> Dispatcher<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `f` with every message that is a `T`.
+    pub fn on<T: SbfBlock + 'a>(self, f: impl FnMut(&T) + 'a) -> Self {
+        self.with_handler(on(f))
+    }
+
+    /// Call `f` with every message.
+    pub fn on_any(self, f: impl FnMut(&Messages) + 'a) -> Self {
+        self.with_handler(on_any(f))
+    }
+
+    /// Call `f` with every error event, see [`Handler::on_error`].
+    pub fn on_error(self, f: impl FnMut(&SbfEvent) + 'a) -> Self {
+        self.with_handler(on_error(f))
+    }
+
+    /// Add a handler of any type, such as a subsystem implementing
+    /// [`Handler`] itself.
+    pub fn with_handler(mut self, handler: impl Handler + 'a) -> Self {
+        self.handlers.push(Box::new(handler));
+        self
+    }
+
+    /// Feed `input` to `parser` and dispatch every event it yields, until the
+    /// parser needs more data.
+    pub fn feed<B: ParserBuffer>(&mut self, parser: &mut SbfParser<B>, input: &[u8]) {
+        dispatch(parser, input, self)
+    }
+
+    /// Dispatch everything `reader` yields until its end, and return the
+    /// parser counters.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn run<R: std::io::Read>(
+        &mut self,
+        reader: crate::reader::SbfReader<R>,
+    ) -> std::io::Result<crate::parser::ParserStats> {
+        let mut events = reader.events();
+        for event in &mut events {
+            self.on_event(&event?);
+        }
+        Ok(events.stats())
+    }
+}
+
+impl Handler for Dispatcher<'_> {
+    fn on_message(&mut self, msg: &Messages) {
+        for handler in &mut self.handlers {
+            handler.on_message(msg);
+        }
+    }
+
+    fn on_error(&mut self, event: &SbfEvent) {
+        for handler in &mut self.handlers {
+            handler.on_error(event);
+        }
+    }
+
+    fn on_event(&mut self, event: &SbfEvent) {
+        for handler in &mut self.handlers {
+            handler.on_event(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dispatch, on, on_any, on_error, Dispatcher};
+    use crate::parser::{SbfEvent, SbfParser};
+    use crate::test_fixtures::VALID_QUALITY_IND;
+    use crate::{Messages, QualityInd, DOP};
+
+    use alloc::vec::Vec;
+
+    fn stream() -> Vec<u8> {
+        let mut bad_crc = *VALID_QUALITY_IND;
+        bad_crc[2] ^= 0xFF;
+        let mut stream = VALID_QUALITY_IND.to_vec();
+        stream.extend_from_slice(&bad_crc);
+        stream.extend_from_slice(VALID_QUALITY_IND);
+        stream
+    }
+
+    #[test]
+    fn test_dispatcher_routes_by_block_type() {
+        let mut tows = Vec::new();
+        let mut dops = 0;
+        let mut names = Vec::new();
+        let mut errors = Vec::new();
+        let mut dispatcher = Dispatcher::new()
+            .on::<QualityInd>(|qi| tows.push(qi.tow))
+            .on::<DOP>(|_| dops += 1)
+            .on_any(|msg: &Messages| names.push(msg.type_name()))
+            .on_error(|event| errors.push(matches!(event, SbfEvent::CrcError { .. })));
+
+        let mut parser = SbfParser::new();
+        for byte in stream() {
+            dispatcher.feed(&mut parser, &[byte]);
+        }
+        drop(dispatcher);
+
+        assert_eq!(tows, [Some(490403000); 2]);
+        assert_eq!(dops, 0);
+        assert_eq!(names, ["QualityInd"; 2]);
+        assert_eq!(errors, [true]);
+    }
+
+    #[test]
+    fn test_static_dispatch() {
+        let mut qualities = 0;
+        let mut messages = 0;
+        let mut errors = 0;
+        {
+            let mut handlers = (
+                on::<QualityInd, _>(|_| qualities += 1),
+                on_any(|_| messages += 1),
+                on_error(|_| errors += 1),
+            );
+            let mut parser = SbfParser::new();
+            dispatch(&mut parser, &stream(), &mut handlers);
+        }
+
+        assert_eq!((qualities, messages, errors), (2, 2, 1));
+    }
+}
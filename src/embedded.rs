@@ -0,0 +1,251 @@
+//! Readers over [`embedded_io`] and [`embedded_io_async`] byte sources, such
+//! as UARTs and embassy sockets.
+//!
+//! Like [`crate::reader::SbfReader`] they feed an [`SbfParser`] and drain it
+//! of every buffered message before reading more, so they resynchronize and
+//! report discarded data the same way. Pass a [`crate::parser::FixedSbfParser`]
+//! to [`SbfReader::with_parser`] to read without a heap-allocated buffer.
+
+use crate::parser::{ParserBuffer, ParserStats, SbfEvent, SbfParser};
+use crate::Messages;
+
+use alloc::vec::Vec;
+
+/// Bytes read from the source at a time.
+const CHUNK_SIZE: usize = 256;
+
+/// Read SBF data from an [`embedded_io::Read`] source.
+///
+/// Iterating yields messages until the source reports end of file, which a
+/// UART never does.
+#[cfg(feature = "embedded-io")]
+#[cfg_attr(docsrs, doc(cfg(feature = "embedded-io")))]
+pub struct SbfReader<R, B = Vec<u8>> {
+    reader: R,
+    parser: SbfParser<B>,
+    drain_internal: bool,
+}
+
+#[cfg(feature = "embedded-io")]
+impl<R: embedded_io::Read> SbfReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_parser(reader, SbfParser::new())
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl<R: embedded_io::Read, B: ParserBuffer> SbfReader<R, B> {
+    /// A reader that feeds `parser`, e.g. a fixed-capacity one.
+    pub fn with_parser(reader: R, parser: SbfParser<B>) -> Self {
+        Self {
+            reader,
+            parser,
+            drain_internal: false,
+        }
+    }
+
+    /// Counters for everything the underlying parser has consumed so far.
+    pub fn stats(&self) -> ParserStats {
+        self.parser.stats()
+    }
+
+    /// Turn the reader into an iterator over parser events, which reports
+    /// discarded data along with the messages.
+    pub fn events(self) -> SbfEvents<R, B> {
+        SbfEvents { inner: self }
+    }
+
+    /// Read from the source until `parse` yields an item, or EOF.
+    fn next_with<T>(
+        &mut self,
+        mut parse: impl FnMut(&mut SbfParser<B>, &[u8]) -> Option<T>,
+    ) -> Option<Result<T, R::Error>> {
+        let mut buffer = [0u8; CHUNK_SIZE];
+        loop {
+            // Drain buffered messages before reading more.
+            if self.drain_internal {
+                if let Some(item) = parse(&mut self.parser, &[]) {
+                    return Some(Ok(item));
+                }
+                self.drain_internal = false;
+            }
+
+            // Never read more than a fixed-capacity parser can take.
+            let len = CHUNK_SIZE.min(self.parser.remaining_capacity()).max(1);
+            let bytes_read = match self.reader.read(&mut buffer[..len]) {
+                Ok(0) => return None,
+                Ok(n) => n,
+                Err(e) => return Some(Err(e)),
+            };
+            if let Some(item) = parse(&mut self.parser, &buffer[..bytes_read]) {
+                self.drain_internal = true;
+                return Some(Ok(item));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl<R: embedded_io::Read, B: ParserBuffer> Iterator for SbfReader<R, B> {
+    type Item = Result<Messages, R::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(SbfParser::consume)
+    }
+}
+
+/// Iterator over the [`SbfEvent`]s of an [`SbfReader`], created by
+/// [`SbfReader::events`].
+#[cfg(feature = "embedded-io")]
+#[cfg_attr(docsrs, doc(cfg(feature = "embedded-io")))]
+pub struct SbfEvents<R, B = Vec<u8>> {
+    inner: SbfReader<R, B>,
+}
+
+#[cfg(feature = "embedded-io")]
+impl<R: embedded_io::Read, B: ParserBuffer> SbfEvents<R, B> {
+    /// Counters for everything the underlying parser has consumed so far.
+    pub fn stats(&self) -> ParserStats {
+        self.inner.stats()
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl<R: embedded_io::Read, B: ParserBuffer> Iterator for SbfEvents<R, B> {
+    type Item = Result<SbfEvent, R::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_with(SbfParser::consume_event)
+    }
+}
+
+/// Read SBF data from an [`embedded_io_async::Read`] source.
+///
+/// `next` and `next_event` return None once the source reports end of
+/// file, which a UART or open socket never does.
+#[cfg(feature = "embedded-io-async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "embedded-io-async")))]
+pub struct AsyncSbfReader<R, B = Vec<u8>> {
+    reader: R,
+    parser: SbfParser<B>,
+    drain_internal: bool,
+}
+
+#[cfg(feature = "embedded-io-async")]
+impl<R: embedded_io_async::Read> AsyncSbfReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_parser(reader, SbfParser::new())
+    }
+}
+
+#[cfg(feature = "embedded-io-async")]
+impl<R: embedded_io_async::Read, B: ParserBuffer> AsyncSbfReader<R, B> {
+    /// A reader that feeds `parser`, e.g. a fixed-capacity one.
+    pub fn with_parser(reader: R, parser: SbfParser<B>) -> Self {
+        Self {
+            reader,
+            parser,
+            drain_internal: false,
+        }
+    }
+
+    /// Counters for everything the underlying parser has consumed so far.
+    pub fn stats(&self) -> ParserStats {
+        self.parser.stats()
+    }
+
+    /// The next message.
+    pub async fn next(&mut self) -> Option<Result<Messages, R::Error>> {
+        self.next_with(SbfParser::consume).await
+    }
+
+    /// The next message or report of discarded data, see [`SbfEvent`].
+    pub async fn next_event(&mut self) -> Option<Result<SbfEvent, R::Error>> {
+        self.next_with(SbfParser::consume_event).await
+    }
+
+    /// Read from the source until `parse` yields an item, or EOF.
+    async fn next_with<T>(
+        &mut self,
+        mut parse: impl FnMut(&mut SbfParser<B>, &[u8]) -> Option<T>,
+    ) -> Option<Result<T, R::Error>> {
+        let mut buffer = [0u8; CHUNK_SIZE];
+        loop {
+            // Drain buffered messages before reading more.
+            if self.drain_internal {
+                if let Some(item) = parse(&mut self.parser, &[]) {
+                    return Some(Ok(item));
+                }
+                self.drain_internal = false;
+            }
+
+            // Never read more than a fixed-capacity parser can take.
+            let len = CHUNK_SIZE.min(self.parser.remaining_capacity()).max(1);
+            let bytes_read = match self.reader.read(&mut buffer[..len]).await {
+                Ok(0) => return None,
+                Ok(n) => n,
+                Err(e) => return Some(Err(e)),
+            };
+            if let Some(item) = parse(&mut self.parser, &buffer[..bytes_read]) {
+                self.drain_internal = true;
+                return Some(Ok(item));
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "embedded-io", feature = "embedded-io-async"))]
+mod tests {
+    use super::{AsyncSbfReader, SbfReader};
+    use crate::parser::{FixedSbfParser, SbfEvent};
+    use crate::test_fixtures::VALID_QUALITY_IND;
+    use crate::Messages;
+
+    /// Noise, then ten QualityInd blocks, so reads straddle block boundaries.
+    fn stream() -> Vec<u8> {
+        let mut stream = b"noise".to_vec();
+        for _ in 0..10 {
+            stream.extend_from_slice(VALID_QUALITY_IND);
+        }
+        stream
+    }
+
+    #[test]
+    fn test_blocking_reader() {
+        let stream = stream();
+        let reader = SbfReader::new(&stream[..]);
+        let messages: Vec<_> = reader.map(Result::unwrap).collect();
+        assert_eq!(messages.len(), 10);
+        assert!(messages
+            .iter()
+            .all(|m| matches!(m, Messages::QualityInd(_))));
+
+        let mut events =
+            SbfReader::with_parser(&stream[..], FixedSbfParser::<64>::default()).events();
+        assert!(matches!(
+            events.next(),
+            Some(Ok(SbfEvent::Skipped { bytes: 5 }))
+        ));
+        assert_eq!(events.by_ref().count(), 10);
+        assert_eq!(events.stats().messages, 10);
+    }
+
+    #[tokio::test]
+    async fn test_async_reader() {
+        let stream = stream();
+        let mut reader = AsyncSbfReader::new(&stream[..]);
+        let mut messages = 0;
+        while let Some(msg) = reader.next().await {
+            assert!(matches!(msg, Ok(Messages::QualityInd(_))));
+            messages += 1;
+        }
+        assert_eq!(messages, 10);
+        assert_eq!(reader.stats().bytes_skipped, 5);
+
+        let mut reader = AsyncSbfReader::with_parser(&stream[..], FixedSbfParser::<64>::default());
+        assert!(matches!(
+            reader.next_event().await,
+            Some(Ok(SbfEvent::Skipped { bytes: 5 }))
+        ));
+    }
+}
@@ -0,0 +1,217 @@
+extern crate alloc;
+use alloc::vec::Vec;
+
+use binrw::io::Cursor;
+use core::fmt;
+
+use crate::Messages;
+
+use crc16::*;
+
+/// Size of the framing in front of a block body: 2 sync bytes, then the CRC,
+/// block ID and length fields of the header.
+const FRAME_HEADER_SIZE: usize = 8;
+
+/// Error type for [`Messages::encode`] and [`Messages::encode_into`].
+#[derive(Debug)]
+pub enum EncodeError {
+    /// The output buffer cannot hold the framed block.
+    BufferTooSmall,
+    /// The framed block is longer than the 16-bit length field can express.
+    TooLong(usize),
+    /// The message's `revision` disagrees with which of its revision-gated
+    /// fields are set, or does not fit the 3 revision bits of the block ID.
+    RevisionMismatch { field: &'static str, revision: u8 },
+    /// Failed to serialize the message body.
+    BinRWError(binrw::Error),
+}
+
+impl From<binrw::Error> for EncodeError {
+    fn from(e: binrw::Error) -> Self {
+        EncodeError::BinRWError(e)
+    }
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::BufferTooSmall => write!(f, "output buffer too small for the block"),
+            EncodeError::TooLong(len) => {
+                write!(f, "block of {len} bytes exceeds the SBF length field")
+            }
+            EncodeError::RevisionMismatch { field, revision } => {
+                write!(f, "field {field} does not match block revision {revision}")
+            }
+            EncodeError::BinRWError(e) => write!(f, "failed to serialize the block: {e}"),
+        }
+    }
+}
+
+impl core::error::Error for EncodeError {}
+
+/// Check a field added in block revision `since` whose `None` is written as
+/// its do-not-use value: it can only be set from that revision on.
+pub(crate) fn check_optional(
+    field: &'static str,
+    is_some: bool,
+    since: u8,
+    revision: u8,
+) -> Result<(), EncodeError> {
+    if is_some && revision < since {
+        return Err(EncodeError::RevisionMismatch { field, revision });
+    }
+    Ok(())
+}
+
+/// Check a field added in block revision `since` that has no do-not-use
+/// value: it must be set exactly from that revision on.
+pub(crate) fn check_required(
+    field: &'static str,
+    is_some: bool,
+    since: u8,
+    revision: u8,
+) -> Result<(), EncodeError> {
+    if is_some != (revision >= since) {
+        return Err(EncodeError::RevisionMismatch { field, revision });
+    }
+    Ok(())
+}
+
+/// Frame a body that has already been written at `buf[8..8 + body_len]`:
+/// zero-pad it to a multiple of 4, then fill in the `$@` sync, XMODEM CRC,
+/// block ID and length. Returns the total framed size.
+pub(crate) fn frame_block(
+    buf: &mut [u8],
+    block_id: u16,
+    body_len: usize,
+) -> Result<usize, EncodeError> {
+    // NOTE: The length field counts the sync, crc, and id fields too, and
+    // must be a multiple of 4.
+    let total = (FRAME_HEADER_SIZE + body_len).next_multiple_of(4);
+    let length = u16::try_from(total).map_err(|_| EncodeError::TooLong(total))?;
+    if buf.len() < total {
+        return Err(EncodeError::BufferTooSmall);
+    }
+
+    buf[FRAME_HEADER_SIZE + body_len..total].fill(0);
+    buf[0..2].copy_from_slice(b"$@");
+    buf[4..6].copy_from_slice(&block_id.to_le_bytes());
+    buf[6..8].copy_from_slice(&length.to_le_bytes());
+
+    // CRC covers block_id + length + payload
+    let crc = State::<XMODEM>::calculate(&buf[4..total]);
+    buf[2..4].copy_from_slice(&crc.to_le_bytes());
+
+    Ok(total)
+}
+
+impl Messages {
+    /// Check that the revision fits the block ID and agrees with the
+    /// revision-gated fields, which would otherwise be dropped or written as
+    /// zeros.
+    fn check_revision(&self) -> Result<(), EncodeError> {
+        let revision = self.revision();
+        if revision > 7 {
+            return Err(EncodeError::RevisionMismatch {
+                field: "revision",
+                revision,
+            });
+        }
+        match self {
+            Messages::PVTCartesian(m) => m.check_revision(),
+            Messages::PVTGeodetic(m) => m.check_revision(),
+            Messages::ReceiverSetup(m) => m.check_revision(),
+            Messages::ExtEvent(m) => m.check_revision(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Serialize the message into a complete SBF block: `$@` sync, CRC,
+    /// block ID, length and the body padded to a multiple of 4 bytes.
+    ///
+    /// The output is accepted by [`crate::parser::SbfParser`] and
+    /// [`crate::parse_datagram`]. The block is written with the message's
+    /// revision, which must agree with the revision-gated fields; otherwise
+    /// [`EncodeError::RevisionMismatch`] is returned.
+    pub fn encode(&self) -> Result<Vec<u8>, EncodeError> {
+        self.check_revision()?;
+        let mut cursor = Cursor::new(Vec::new());
+        cursor.get_mut().resize(FRAME_HEADER_SIZE, 0);
+        cursor.set_position(FRAME_HEADER_SIZE as u64);
+        self.write_body(&mut cursor)?;
+
+        let mut buf = cursor.into_inner();
+        let body_len = buf.len() - FRAME_HEADER_SIZE;
+        buf.resize((FRAME_HEADER_SIZE + body_len).next_multiple_of(4), 0);
+        let total = frame_block(&mut buf, self.block_id(), body_len)?;
+        buf.truncate(total);
+        Ok(buf)
+    }
+
+    /// Serialize the message into `buf` without allocating for the frame,
+    /// returning the number of bytes written. See [`Messages::encode`].
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        self.check_revision()?;
+        if buf.len() < FRAME_HEADER_SIZE {
+            return Err(EncodeError::BufferTooSmall);
+        }
+
+        let body_len = {
+            let mut cursor = Cursor::new(&mut buf[FRAME_HEADER_SIZE..]);
+            self.write_body(&mut cursor).map_err(|e| match e {
+                EncodeError::BinRWError(binrw::Error::Io(_)) => EncodeError::BufferTooSmall,
+                e => e,
+            })?;
+            cursor.position() as usize
+        };
+
+        frame_block(buf, self.block_id(), body_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EncodeError;
+    use crate::parser::{parse_datagram, SbfParser};
+    use crate::test_fixtures::VALID_QUALITY_IND;
+    use crate::Messages;
+
+    #[test]
+    fn test_encode_reproduces_block() {
+        let msg = parse_datagram(VALID_QUALITY_IND).unwrap();
+        let bytes = msg.encode().unwrap();
+        assert_eq!(&bytes[..], &VALID_QUALITY_IND[..]);
+    }
+
+    #[test]
+    fn test_encode_into_matches_encode() {
+        let msg = parse_datagram(VALID_QUALITY_IND).unwrap();
+        let mut buf = [0u8; 64];
+        let n = msg.encode_into(&mut buf).unwrap();
+        assert_eq!(&buf[..n], &msg.encode().unwrap()[..]);
+    }
+
+    #[test]
+    fn test_encode_into_buffer_too_small() {
+        let msg = parse_datagram(VALID_QUALITY_IND).unwrap();
+        let mut buf = [0u8; 20];
+        assert!(matches!(
+            msg.encode_into(&mut buf),
+            Err(EncodeError::BufferTooSmall)
+        ));
+    }
+
+    #[test]
+    fn test_encoded_block_streams_back() {
+        let msg = parse_datagram(VALID_QUALITY_IND).unwrap();
+        let mut stream = msg.encode().unwrap();
+        stream.extend(msg.encode().unwrap());
+
+        let mut parser = SbfParser::new();
+        assert!(matches!(
+            parser.consume(&stream),
+            Some(Messages::QualityInd(_))
+        ));
+        assert!(matches!(parser.consume(&[]), Some(Messages::QualityInd(_))));
+    }
+}
@@ -0,0 +1,423 @@
+//! Group the blocks of one epoch into a single bundle.
+//!
+//! A receiver outputs the PVT, measurement and attitude blocks of an epoch
+//! back to back, all with the same TOW and WNc, and can close each group with
+//! an `EndOfPVT`, `EndOfMeas` or `EndOfAtt` block. [`EpochAssembler`] collects
+//! the blocks of each group and hands out a bundle once the group is over:
+//!
+//! - on its end marker, as a complete epoch;
+//! - when a block of the group arrives with a new time stamp, in case the end
+//!   markers are not enabled;
+//! - with [`EpochAssembler::with_timeout`], once a block of any kind arrives
+//!   that much later, in case the group stops;
+//! - on [`EpochAssembler::flush`].
+//!
+//! All but the first are reported as incomplete, since blocks may be
+//! missing.
+//!
+//! ```no_run
+//! # #[cfg(feature = "std")]
+//! # fn main() -> std::io::Result<()> {
+//! use libsbf::epoch::{Epoch, EpochAssembler};
+//! use libsbf::reader::SbfReader;
+//! use std::fs::File;
+//!
+//! let mut epochs = EpochAssembler::new();
+//! for msg in SbfReader::new(File::open("log.sbf")?) {
+//!     epochs.push(msg?);
+//!     while let Some(epoch) = epochs.next_epoch() {
+//!         if let Epoch::Pvt(pvt) = epoch {
+//!             println!("{} {:?} {:?}", pvt.time, pvt.pvt_geodetic, pvt.dop);
+//!         }
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "std"))]
+//! # fn main() {}
+//! ```
+
+use crate::time::GnssTime;
+use crate::{
+    AttCovEuler, AttEuler, AuxAntPositions, BaseVectorCart, BaseVectorGeod, Meas3Doppler,
+    Meas3Ranges, MeasEpoch, MeasExtra, Messages, PVTCartesian, PVTGeodetic, PosCart,
+    PosCovCartesian, PosCovGeodetic, VelCovCartesian, VelCovGeodetic, DOP,
+};
+
+use alloc::collections::VecDeque;
+use core::time::Duration;
+
+/// The PVT blocks of one epoch, closed by `EndOfPVT`.
+#[derive(Clone, Debug)]
+pub struct PvtEpoch {
+    pub time: GnssTime,
+    /// Whether the epoch was closed by its `EndOfPVT` block.
+    pub complete: bool,
+    pub pvt_cartesian: Option<PVTCartesian>,
+    pub pvt_geodetic: Option<PVTGeodetic>,
+    pub pos_cov_cartesian: Option<PosCovCartesian>,
+    pub pos_cov_geodetic: Option<PosCovGeodetic>,
+    pub vel_cov_cartesian: Option<VelCovCartesian>,
+    pub vel_cov_geodetic: Option<VelCovGeodetic>,
+    pub dop: Option<DOP>,
+    pub pos_cart: Option<PosCart>,
+    pub base_vector_cart: Option<BaseVectorCart>,
+    pub base_vector_geod: Option<BaseVectorGeod>,
+}
+
+/// The measurement blocks of one epoch, closed by `EndOfMeas`.
+#[derive(Clone, Debug)]
+pub struct MeasEpochSet {
+    pub time: GnssTime,
+    /// Whether the epoch was closed by its `EndOfMeas` block.
+    pub complete: bool,
+    pub meas_epoch: Option<MeasEpoch>,
+    pub meas_extra: Option<MeasExtra>,
+    pub meas3_ranges: Option<Meas3Ranges>,
+    pub meas3_doppler: Option<Meas3Doppler>,
+}
+
+/// The attitude blocks of one epoch, closed by `EndOfAtt`.
+#[derive(Clone, Debug)]
+pub struct AttEpoch {
+    pub time: GnssTime,
+    /// Whether the epoch was closed by its `EndOfAtt` block.
+    pub complete: bool,
+    pub att_euler: Option<AttEuler>,
+    pub att_cov_euler: Option<AttCovEuler>,
+    pub aux_ant_positions: Option<AuxAntPositions>,
+}
+
+/// A bundle handed out by [`EpochAssembler`].
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug)]
+pub enum Epoch {
+    Pvt(PvtEpoch),
+    Meas(MeasEpochSet),
+    Att(AttEpoch),
+}
+
+impl Epoch {
+    pub fn time(&self) -> GnssTime {
+        match self {
+            Epoch::Pvt(e) => e.time,
+            Epoch::Meas(e) => e.time,
+            Epoch::Att(e) => e.time,
+        }
+    }
+
+    /// Whether the epoch was closed by its end marker, rather than by a new
+    /// time stamp, a timeout or a flush.
+    pub fn is_complete(&self) -> bool {
+        match self {
+            Epoch::Pvt(e) => e.complete,
+            Epoch::Meas(e) => e.complete,
+            Epoch::Att(e) => e.complete,
+        }
+    }
+}
+
+/// A group of blocks being collected.
+trait Bundle: Sized {
+    fn new(time: GnssTime) -> Self;
+    fn time(&self) -> GnssTime;
+    fn into_epoch(self, complete: bool) -> Epoch;
+}
+
+impl Bundle for PvtEpoch {
+    fn new(time: GnssTime) -> Self {
+        Self {
+            time,
+            complete: false,
+            pvt_cartesian: None,
+            pvt_geodetic: None,
+            pos_cov_cartesian: None,
+            pos_cov_geodetic: None,
+            vel_cov_cartesian: None,
+            vel_cov_geodetic: None,
+            dop: None,
+            pos_cart: None,
+            base_vector_cart: None,
+            base_vector_geod: None,
+        }
+    }
+
+    fn time(&self) -> GnssTime {
+        self.time
+    }
+
+    fn into_epoch(self, complete: bool) -> Epoch {
+        Epoch::Pvt(Self { complete, ..self })
+    }
+}
+
+impl Bundle for MeasEpochSet {
+    fn new(time: GnssTime) -> Self {
+        Self {
+            time,
+            complete: false,
+            meas_epoch: None,
+            meas_extra: None,
+            meas3_ranges: None,
+            meas3_doppler: None,
+        }
+    }
+
+    fn time(&self) -> GnssTime {
+        self.time
+    }
+
+    fn into_epoch(self, complete: bool) -> Epoch {
+        Epoch::Meas(Self { complete, ..self })
+    }
+}
+
+impl Bundle for AttEpoch {
+    fn new(time: GnssTime) -> Self {
+        Self {
+            time,
+            complete: false,
+            att_euler: None,
+            att_cov_euler: None,
+            aux_ant_positions: None,
+        }
+    }
+
+    fn time(&self) -> GnssTime {
+        self.time
+    }
+
+    fn into_epoch(self, complete: bool) -> Epoch {
+        Epoch::Att(Self { complete, ..self })
+    }
+}
+
+/// Collects PVT, measurement and attitude blocks into epochs. See the
+/// [module docs](self).
+#[derive(Debug, Default)]
+pub struct EpochAssembler {
+    pvt: Option<PvtEpoch>,
+    meas: Option<MeasEpochSet>,
+    att: Option<AttEpoch>,
+    timeout: Option<Duration>,
+    ready: VecDeque<Epoch>,
+}
+
+impl EpochAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hand out an epoch as incomplete once a block stamped `timeout` or more
+    /// after it arrives.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Add a message. Messages that belong to no epoch, or have no time
+    /// stamp, are given back.
+    pub fn push(&mut self, msg: Messages) -> Option<Messages> {
+        let Some(time) = msg.time() else {
+            return Some(msg);
+        };
+        self.expire(time);
+
+        let ready = &mut self.ready;
+        match msg {
+            Messages::EndOfPVT(_) => close(&mut self.pvt, ready, Some(time)),
+            Messages::EndOfMeas(_) => close(&mut self.meas, ready, Some(time)),
+            Messages::EndOfAtt(_) => close(&mut self.att, ready, Some(time)),
+
+            Messages::PVTCartesian(m) => slot(&mut self.pvt, ready, time).pvt_cartesian = Some(m),
+            Messages::PVTGeodetic(m) => slot(&mut self.pvt, ready, time).pvt_geodetic = Some(m),
+            Messages::PosCovCartesian(m) => {
+                slot(&mut self.pvt, ready, time).pos_cov_cartesian = Some(m)
+            }
+            Messages::PosCovGeodetic(m) => {
+                slot(&mut self.pvt, ready, time).pos_cov_geodetic = Some(m)
+            }
+            Messages::VelCovCartesian(m) => {
+                slot(&mut self.pvt, ready, time).vel_cov_cartesian = Some(m)
+            }
+            Messages::VelCovGeodetic(m) => {
+                slot(&mut self.pvt, ready, time).vel_cov_geodetic = Some(m)
+            }
+            Messages::DOP(m) => slot(&mut self.pvt, ready, time).dop = Some(m),
+            Messages::PosCart(m) => slot(&mut self.pvt, ready, time).pos_cart = Some(m),
+            Messages::BaseVectorCart(m) => {
+                slot(&mut self.pvt, ready, time).base_vector_cart = Some(m)
+            }
+            Messages::BaseVectorGeod(m) => {
+                slot(&mut self.pvt, ready, time).base_vector_geod = Some(m)
+            }
+
+            Messages::MeasEpoch(m) => slot(&mut self.meas, ready, time).meas_epoch = Some(m),
+            Messages::MeasExtra(m) => slot(&mut self.meas, ready, time).meas_extra = Some(m),
+            Messages::Meas3Ranges(m) => slot(&mut self.meas, ready, time).meas3_ranges = Some(m),
+            Messages::Meas3Doppler(m) => slot(&mut self.meas, ready, time).meas3_doppler = Some(m),
+
+            Messages::AttEuler(m) => slot(&mut self.att, ready, time).att_euler = Some(m),
+            Messages::AttCovEuler(m) => slot(&mut self.att, ready, time).att_cov_euler = Some(m),
+            Messages::AuxAntPositions(m) => {
+                slot(&mut self.att, ready, time).aux_ant_positions = Some(m)
+            }
+
+            other => return Some(other),
+        }
+        None
+    }
+
+    /// The next epoch that is over, oldest first.
+    pub fn next_epoch(&mut self) -> Option<Epoch> {
+        self.ready.pop_front()
+    }
+
+    /// Hand out the epochs still being collected as incomplete, e.g. at the
+    /// end of a log.
+    pub fn flush(&mut self) {
+        let mut pending = [
+            self.pvt.take().map(|e| e.into_epoch(false)),
+            self.meas.take().map(|e| e.into_epoch(false)),
+            self.att.take().map(|e| e.into_epoch(false)),
+        ];
+        pending.sort_by_key(|e| e.as_ref().map(Epoch::time));
+        self.ready.extend(pending.into_iter().flatten());
+    }
+
+    /// Hand out the epochs a block stamped `now` has timed out.
+    fn expire(&mut self, now: GnssTime) {
+        let Some(timeout) = self.timeout else {
+            return;
+        };
+        let timeout = timeout.as_millis() as i64;
+        let expired = |time: GnssTime| now.ms_since(time) >= timeout;
+        if self.pvt.as_ref().is_some_and(|e| expired(e.time)) {
+            close(&mut self.pvt, &mut self.ready, None);
+        }
+        if self.meas.as_ref().is_some_and(|e| expired(e.time)) {
+            close(&mut self.meas, &mut self.ready, None);
+        }
+        if self.att.as_ref().is_some_and(|e| expired(e.time)) {
+            close(&mut self.att, &mut self.ready, None);
+        }
+    }
+}
+
+/// The epoch being collected for `time`, handing out the previous one as
+/// incomplete if it has a different time.
+fn slot<'a, T: Bundle>(
+    pending: &'a mut Option<T>,
+    ready: &mut VecDeque<Epoch>,
+    time: GnssTime,
+) -> &'a mut T {
+    if pending.as_ref().is_some_and(|e| e.time() != time) {
+        close(pending, ready, None);
+    }
+    pending.get_or_insert_with(|| T::new(time))
+}
+
+/// Hand out the epoch being collected, complete if closed by an end marker
+/// stamped with its time.
+fn close<T: Bundle>(
+    pending: &mut Option<T>,
+    ready: &mut VecDeque<Epoch>,
+    marker: Option<GnssTime>,
+) {
+    if let Some(epoch) = pending.take() {
+        let complete = marker == Some(epoch.time());
+        ready.push_back(epoch.into_epoch(complete));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Epoch, EpochAssembler};
+    use crate::{EndOfMeas, EndOfPVT, Messages, DOP};
+
+    use alloc::vec::Vec;
+    use core::time::Duration;
+
+    fn dop(tow: u32) -> Messages {
+        Messages::DOP(DOP {
+            tow: Some(tow),
+            wnc: Some(2360),
+            nr_sv: Some(9),
+            reserved: 0,
+            pdop: Some(150),
+            tdop: None,
+            hdop: None,
+            vdop: None,
+            hpl: None,
+            vpl: None,
+            revision: 0,
+        })
+    }
+
+    fn end_of_pvt(tow: u32) -> Messages {
+        Messages::EndOfPVT(EndOfPVT {
+            tow: Some(tow),
+            wnc: Some(2360),
+            padding: Vec::new(),
+            revision: 0,
+        })
+    }
+
+    fn end_of_meas(tow: Option<u32>) -> Messages {
+        Messages::EndOfMeas(EndOfMeas {
+            tow,
+            wnc: Some(2360),
+            padding: Vec::new(),
+            revision: 0,
+        })
+    }
+
+    fn drain(epochs: &mut EpochAssembler) -> Vec<(u32, bool)> {
+        core::iter::from_fn(|| epochs.next_epoch())
+            .map(|e| (e.time().tow_ms(), e.is_complete()))
+            .collect()
+    }
+
+    #[test]
+    fn test_epochs_close_on_markers_and_new_times() {
+        let mut epochs = EpochAssembler::new();
+        assert!(epochs.push(dop(1000)).is_none());
+        assert!(epochs.push(end_of_pvt(1000)).is_none());
+        match epochs.next_epoch() {
+            Some(Epoch::Pvt(pvt)) => {
+                assert!(pvt.complete);
+                assert_eq!(pvt.dop.and_then(|d| d.pdop), Some(150));
+                assert!(pvt.pvt_geodetic.is_none());
+            }
+            other => panic!("expected a PVT epoch, got {other:?}"),
+        }
+
+        // Without the marker, the next epoch closes the previous one.
+        epochs.push(dop(2000));
+        assert!(epochs.next_epoch().is_none());
+        epochs.push(dop(3000));
+        assert_eq!(drain(&mut epochs), [(2000, false)]);
+
+        // A marker for another time closes the epoch as incomplete.
+        epochs.push(end_of_pvt(4000));
+        assert_eq!(drain(&mut epochs), [(3000, false)]);
+
+        // Blocks without a time stamp are given back.
+        assert!(epochs.push(end_of_meas(None)).is_some());
+    }
+
+    #[test]
+    fn test_epochs_time_out_and_flush() {
+        let mut epochs = EpochAssembler::new().with_timeout(Duration::from_millis(500));
+        epochs.push(dop(1000));
+        // Any block late enough times the epoch out.
+        epochs.push(end_of_meas(Some(1400)));
+        assert!(epochs.next_epoch().is_none());
+        epochs.push(end_of_meas(Some(1500)));
+        assert_eq!(drain(&mut epochs), [(1000, false)]);
+
+        epochs.push(dop(3000));
+        epochs.flush();
+        assert_eq!(drain(&mut epochs), [(3000, false)]);
+    }
+}
@@ -0,0 +1,466 @@
+//! A time index over SBF log files, for jumping to a GNSS time or block
+//! number without decoding everything before it.
+//!
+//! [`SbfIndex::build`] scans a log once, CRC-checking every block without
+//! decoding it, and records where each block starts along with its ID and
+//! time stamp. The index can be saved next to the log and loaded again, and
+//! used to seek a reader to the blocks of interest.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use libsbf::index::SbfIndex;
+//! use libsbf::{INSNavGeod, SbfBlock};
+//! use std::fs::File;
+//!
+//! fn main() -> std::io::Result<()> {
+//!     let index = SbfIndex::for_log("log.sbf")?;
+//!     let mut log = File::open("log.sbf")?;
+//!     // INSNavGeod blocks between TOW 100 s and 200 s of week 2360.
+//!     for entry in index.between((2360, 100_000), (2360, 200_000)) {
+//!         if entry.block_id == INSNavGeod::BLOCK_NUMBER {
+//!             println!("{:?}", SbfIndex::read_entry(&mut log, entry)?);
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use crate::binrw_util;
+use crate::parser::{check_frame, decode_body, BlockFilter, SbfEvent, SbfParser};
+use crate::reader::SbfReader;
+use crate::Messages;
+
+use binrw::{binrw, BinRead, BinResult, BinWrite};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Bytes read from the log at a time while scanning.
+const BUFFER_SIZE: usize = 1024 * 64;
+
+/// Size of a saved [`IndexEntry`].
+const ENTRY_SIZE: u64 = 19;
+
+/// Where one block sits in a log, and its ID and time stamp.
+#[binrw]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexEntry {
+    /// Offset of the block's sync bytes from the start of the log.
+    pub offset: u64,
+    /// Size of the framed block: sync, header, body and padding.
+    pub len: u16,
+    /// Block number, bits 0-12 of the block ID.
+    pub block_id: u16,
+    /// Block revision number, bits 13-15 of the block ID.
+    pub revision: u8,
+    #[br(map = binrw_util::map_u2)]
+    #[bw(map = binrw_util::unmap_u2)]
+    pub wnc: Option<u16>,
+    #[br(map = binrw_util::map_u4)]
+    #[bw(map = binrw_util::unmap_u4)]
+    pub tow: Option<u32>,
+}
+
+impl IndexEntry {
+    /// Read the entry for the CRC-checked block `frame` found at `offset`.
+    fn from_frame(offset: u64, frame: &[u8]) -> Self {
+        let id = u16::from_le_bytes([frame[4], frame[5]]);
+        // The time stamp is the first thing in every block body, but a block
+        // may be no more than a header.
+        let (tow, wnc) = match frame.get(8..14) {
+            Some(stamp) => (
+                binrw_util::map_u4(u32::from_le_bytes([stamp[0], stamp[1], stamp[2], stamp[3]])),
+                binrw_util::map_u2(u16::from_le_bytes([stamp[4], stamp[5]])),
+            ),
+            None => (None, None),
+        };
+        Self {
+            offset,
+            len: frame.len() as u16,
+            block_id: id & 0x1FFF,
+            revision: (id >> 13) as u8,
+            wnc,
+            tow,
+        }
+    }
+
+    /// Week number and time of week in milliseconds, if the block has both.
+    pub fn time(&self) -> Option<(u16, u32)> {
+        Some((self.wnc?, self.tow?))
+    }
+}
+
+/// Every valid block of an SBF log, in file order. See the [module
+/// docs](self).
+///
+/// Lookups by time binary search the blocks that have a time stamp, so they
+/// expect those to be in time order, as a receiver logs them.
+#[binrw]
+#[brw(little, magic = b"SBFIDX02")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SbfIndex {
+    /// Size of the indexed log, to tell when a saved index is stale.
+    log_len: u64,
+    /// Modification time of the indexed log in nanoseconds since the Unix
+    /// epoch, or 0 if unknown, to tell when a saved index is stale.
+    log_modified: u64,
+    #[br(temp)]
+    #[bw(calc = entries.len() as u64)]
+    count: u64,
+    #[br(parse_with = read_entries, args(count))]
+    entries: Vec<IndexEntry>,
+    /// Positions in `entries` of the blocks with a time stamp.
+    #[br(calc = timed_positions(&entries))]
+    #[bw(ignore)]
+    timed: Vec<usize>,
+}
+
+impl SbfIndex {
+    /// Scan `reader` to its end and index every block that passes its CRC
+    /// check. Nothing is decoded, and data between blocks is skipped.
+    pub fn build<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut parser = SbfParser::new().with_filter(BlockFilter::none());
+        let mut entries = Vec::new();
+        let mut buffer = vec![0u8; BUFFER_SIZE];
+        let mut log_len = 0u64;
+        loop {
+            let bytes_read = reader.read(&mut buffer)?;
+            log_len += bytes_read as u64;
+            let mut input = &buffer[..bytes_read];
+            while let Some(event) = parser.consume_event(input) {
+                input = &[];
+                if let SbfEvent::Filtered { offset, .. } = event {
+                    entries.push(IndexEntry::from_frame(offset, parser.frame()));
+                }
+            }
+            if bytes_read == 0 {
+                break;
+            }
+        }
+        Ok(Self::new(log_len, entries))
+    }
+
+    fn new(log_len: u64, entries: Vec<IndexEntry>) -> Self {
+        Self {
+            log_len,
+            log_modified: 0,
+            timed: timed_positions(&entries),
+            entries,
+        }
+    }
+
+    /// Load the index saved next to the log at `path`, or build and save it
+    /// if there is none or the log has changed size or modification time
+    /// since.
+    pub fn for_log(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let index_path = Self::path_for(path);
+        let metadata = std::fs::metadata(path)?;
+        let log_modified = metadata.modified().map_or(0, nanos_since_epoch);
+        if let Ok(index) = Self::load(&index_path) {
+            if index.log_len == metadata.len() && index.log_modified == log_modified {
+                return Ok(index);
+            }
+        }
+
+        let mut index = Self::build(BufReader::new(File::open(path)?))?;
+        index.log_modified = log_modified;
+        index.save(&index_path)?;
+        Ok(index)
+    }
+
+    /// Where [`SbfIndex::for_log`] keeps the index of the log at `path`: the
+    /// same path with `.idx` appended.
+    pub fn path_for(path: impl AsRef<Path>) -> PathBuf {
+        let mut index_path = path.as_ref().as_os_str().to_owned();
+        index_path.push(".idx");
+        index_path.into()
+    }
+
+    /// Read an index written by [`SbfIndex::save`].
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+
+    /// Write the index to `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Read an index written by [`SbfIndex::write_to`].
+    pub fn read_from<R: Read + Seek>(reader: &mut R) -> io::Result<Self> {
+        Self::read(reader).map_err(into_io_error)
+    }
+
+    /// Write the index in its binary format.
+    pub fn write_to<W: Write + Seek>(&self, writer: &mut W) -> io::Result<()> {
+        self.write(writer).map_err(into_io_error)
+    }
+
+    /// Size in bytes of the indexed log.
+    pub fn log_len(&self) -> u64 {
+        self.log_len
+    }
+
+    pub fn entries(&self) -> &[IndexEntry] {
+        &self.entries
+    }
+
+    /// Position in [`SbfIndex::entries`] of the first block stamped at or
+    /// after week `wnc`, `tow` milliseconds.
+    pub fn position_at(&self, wnc: u16, tow: u32) -> Option<usize> {
+        self.timed.get(self.timed_before((wnc, tow))).copied()
+    }
+
+    /// How many of the time-stamped blocks are stamped before `time`.
+    fn timed_before(&self, time: (u16, u32)) -> usize {
+        self.timed
+            .partition_point(|&i| self.entries[i].time().is_some_and(|t| t < time))
+    }
+
+    /// Position in [`SbfIndex::entries`] of the first block numbered
+    /// `block_number`.
+    pub fn position_of(&self, block_number: u16) -> Option<usize> {
+        self.entries.iter().position(|e| e.block_id == block_number)
+    }
+
+    /// The blocks stamped from `start` up to but excluding `end`, each given
+    /// as week number and time of week in milliseconds.
+    pub fn between(
+        &self,
+        start: (u16, u32),
+        end: (u16, u32),
+    ) -> impl Iterator<Item = &IndexEntry> + '_ {
+        let first = self.timed_before(start);
+        let last = self.timed_before(end).max(first);
+        self.timed[first..last].iter().map(|&i| &self.entries[i])
+    }
+
+    /// Seek `reader` over the indexed log to the first block stamped at or
+    /// after `wnc`/`tow` and read from there. The reader is left at the end
+    /// of the log if no block is that late.
+    pub fn seek_to_time<R: Read + Seek>(
+        &self,
+        reader: R,
+        wnc: u16,
+        tow: u32,
+    ) -> io::Result<SbfReader<R>> {
+        self.seek_to(reader, self.position_at(wnc, tow))
+    }
+
+    /// Seek `reader` over the indexed log to the first block numbered
+    /// `block_number` and read from there. The reader is left at the end of
+    /// the log if there is no such block.
+    pub fn seek_to_block<R: Read + Seek>(
+        &self,
+        reader: R,
+        block_number: u16,
+    ) -> io::Result<SbfReader<R>> {
+        self.seek_to(reader, self.position_of(block_number))
+    }
+
+    fn seek_to<R: Read + Seek>(
+        &self,
+        mut reader: R,
+        position: Option<usize>,
+    ) -> io::Result<SbfReader<R>> {
+        let offset = position.map_or(self.log_len, |i| self.entries[i].offset);
+        reader.seek(SeekFrom::Start(offset))?;
+        Ok(SbfReader::new(reader))
+    }
+
+    /// Read and decode the single block `entry` from the indexed log.
+    pub fn read_entry<R: Read + Seek>(reader: &mut R, entry: &IndexEntry) -> io::Result<Messages> {
+        let mut frame = vec![0u8; usize::from(entry.len)];
+        reader.seek(SeekFrom::Start(entry.offset))?;
+        reader.read_exact(&mut frame)?;
+        let (block_id, frame) = check_frame(&frame, frame.len()).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidData, "no valid block at the entry")
+        })?;
+        decode_body(&block_id, &frame[8..]).map_err(into_io_error)
+    }
+}
+
+// Read `count` entries, after checking that the rest of the input can hold
+// that many, so a corrupt count cannot make the reader allocate without
+// bound.
+#[binrw::parser(reader, endian)]
+fn read_entries(count: u64) -> BinResult<Vec<IndexEntry>> {
+    let pos = reader.stream_position()?;
+    let end = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(pos))?;
+    if count
+        .checked_mul(ENTRY_SIZE)
+        .is_none_or(|len| len > end.saturating_sub(pos))
+    {
+        return Err(binrw::Error::AssertFail {
+            pos,
+            message: format!("{count} entries do not fit in the index"),
+        });
+    }
+    (0..count)
+        .map(|_| IndexEntry::read_options(reader, endian, ()))
+        .collect()
+}
+
+fn timed_positions(entries: &[IndexEntry]) -> Vec<usize> {
+    (0..entries.len())
+        .filter(|&i| entries[i].time().is_some())
+        .collect()
+}
+
+fn nanos_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64)
+}
+
+fn into_io_error(error: binrw::Error) -> io::Error {
+    match error {
+        binrw::Error::Io(e) => e,
+        other => io::Error::new(io::ErrorKind::InvalidData, other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SbfIndex;
+    use crate::parser::parse_datagram;
+    use crate::test_fixtures::{long_block, VALID_QUALITY_IND};
+    use crate::Messages;
+
+    use std::fs::{self, File};
+    use std::io::Cursor;
+    use std::time::{Duration, SystemTime};
+
+    /// Noise, then QualityInd blocks one second apart.
+    fn log() -> Vec<u8> {
+        let mut log = b"noise".to_vec();
+        for i in 0..5 {
+            let mut msg = parse_datagram(VALID_QUALITY_IND).unwrap();
+            if let Messages::QualityInd(qi) = &mut msg {
+                qi.tow = Some(1000 * i);
+            }
+            log.extend_from_slice(&msg.encode().unwrap());
+        }
+        log
+    }
+
+    #[test]
+    fn test_index_seeks_by_time() {
+        let log = log();
+        let index = SbfIndex::build(&log[..]).unwrap();
+        assert_eq!(index.log_len(), log.len() as u64);
+        assert_eq!(index.entries().len(), 5);
+        let entry = index.entries()[2];
+        assert_eq!(entry.offset, 5 + 64);
+        assert_eq!((entry.len, entry.block_id, entry.revision), (32, 4082, 0));
+        assert_eq!(entry.time(), Some((2360, 2000)));
+
+        assert_eq!(index.between((2360, 1000), (2360, 3000)).count(), 2);
+        let mut reader = index.seek_to_time(Cursor::new(&log), 2360, 1500).unwrap();
+        match reader.next() {
+            Some(Ok(Messages::QualityInd(qi))) => assert_eq!(qi.tow, Some(2000)),
+            other => panic!("expected QualityInd, got {other:?}"),
+        }
+        assert_eq!(reader.count(), 2);
+        assert!(index
+            .seek_to_time(Cursor::new(&log), 2361, 0)
+            .unwrap()
+            .next()
+            .is_none());
+
+        let msg = SbfIndex::read_entry(&mut Cursor::new(&log), &entry).unwrap();
+        assert_eq!(msg.tow(), Some(2000));
+    }
+
+    #[test]
+    fn test_index_round_trips() {
+        let index = SbfIndex::build(&log()[..]).unwrap();
+        let mut saved = Cursor::new(Vec::new());
+        index.write_to(&mut saved).unwrap();
+        assert_eq!(&saved.get_ref()[..8], b"SBFIDX02");
+        saved.set_position(0);
+        assert_eq!(SbfIndex::read_from(&mut saved).unwrap(), index);
+
+        let mut corrupt = Cursor::new(b"SBFIDX00".to_vec());
+        assert!(SbfIndex::read_from(&mut corrupt).is_err());
+
+        // Entry counts larger than the file holds are rejected before
+        // anything is allocated for them.
+        let saved = saved.into_inner();
+        assert_eq!(saved.len(), 32 + 5 * super::ENTRY_SIZE as usize);
+        for count in [6, u64::MAX / super::ENTRY_SIZE, u64::MAX] {
+            let mut corrupt = saved.clone();
+            corrupt[24..32].copy_from_slice(&count.to_le_bytes());
+            assert!(SbfIndex::read_from(&mut Cursor::new(corrupt)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_index_skips_blocks_without_time() {
+        let mut log = log();
+        let mut msg = parse_datagram(VALID_QUALITY_IND).unwrap();
+        if let Messages::QualityInd(qi) = &mut msg {
+            qi.tow = None;
+        }
+        let untimed = msg.encode().unwrap();
+        log.splice(5 + 64..5 + 64, untimed.iter().copied());
+        log.extend_from_slice(&untimed);
+
+        let index = SbfIndex::build(&log[..]).unwrap();
+        assert_eq!(index.entries().len(), 7);
+        assert_eq!(index.position_at(2360, 1500), Some(3));
+        assert_eq!(index.position_at(2360, 4001), None);
+        let tows: Vec<_> = index
+            .between((2360, 1000), (2360, 4000))
+            .map(|e| e.tow)
+            .collect();
+        assert_eq!(tows, [Some(1000), Some(2000), Some(3000)]);
+        assert_eq!(index.between((2360, 3000), (2360, 1000)).count(), 0);
+    }
+
+    #[test]
+    fn test_index_reads_long_block() {
+        let mut log = log();
+        log.extend_from_slice(&long_block());
+
+        let index = SbfIndex::build(&log[..]).unwrap();
+        let entry = index.entries()[5];
+        assert_eq!((entry.len, entry.block_id), (65532, 4999));
+        match SbfIndex::read_entry(&mut Cursor::new(&log), &entry).unwrap() {
+            Messages::RawBlock(raw) => assert_eq!(raw.payload.len(), 65532 - 14),
+            other => panic!("expected RawBlock, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_index_rebuilt_when_log_modified() {
+        let path = std::env::temp_dir().join(format!("libsbf-index-{}.sbf", std::process::id()));
+        let index_path = SbfIndex::path_for(&path);
+        let mut log = log();
+        fs::write(&path, &log).unwrap();
+        assert_eq!(SbfIndex::for_log(&path).unwrap().entries().len(), 5);
+
+        // Same size, new contents and modification time.
+        log.truncate(log.len() - 32);
+        log.extend_from_slice(&[0; 32]);
+        fs::write(&path, &log).unwrap();
+        let modified = SystemTime::now() + Duration::from_secs(10);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        let index = SbfIndex::for_log(&path).unwrap();
+        assert_eq!(index.entries().len(), 4);
+        assert_eq!(SbfIndex::load(&index_path).unwrap(), index);
+
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&index_path).unwrap();
+    }
+}
@@ -5,6 +5,67 @@
 //! There is also a `std` API that exposes an `SbfReader` that uses a
 //! BufReader. The `SbfReader` implements an `Iterator` that will give
 //! you `libsbf::Messages`. To enable this do `cargo add libsbf -F std`
+//!
+//! ## Log file index
+//! With `std`, [`index::SbfIndex`] indexes a log file by block and GNSS time,
+//! so a reader can seek straight to the blocks it needs.
+//!
+//! ## GNSS time
+//! [`Messages::time`] combines a block's TOW and WNc into a
+//! [`time::GnssTime`], which converts to UTC given the leap seconds, and with
+//! the `chrono` or `time` feature to their date and time types.
+//!
+//! ## Block revisions
+//! Each message keeps the revision of the block it was decoded from, bits
+//! 13-15 of the block ID, and is encoded with it. [`PVTCartesian`],
+//! [`PVTGeodetic`], [`ReceiverSetup`] and [`ExtEvent`] read the fields later
+//! revisions added only from blocks of those revisions, and leave them
+//! `None` otherwise; [`Messages::encode`] rejects a message whose revision
+//! disagrees with them. Every other block is decoded with the same layout
+//! whatever its revision, so fields a newer revision appends are not read.
+//!
+//! ## Epochs
+//! [`epoch::EpochAssembler`] bundles the PVT, measurement and attitude
+//! blocks of each epoch, using the `EndOfPVT`, `EndOfMeas` and `EndOfAtt`
+//! markers to tell when an epoch is complete.
+//!
+//! ## Observations
+//! [`MeasEpoch::observations`] decodes pseudorange, carrier phase, Doppler
+//! and C/N0 per satellite and [`signal::SignalType`], scaled as the SBF
+//! reference guide defines them.
+//!
+//! ## Dispatching
+//! [`dispatch::Dispatcher`] calls handlers registered per block type, so one
+//! stream can feed several subsystems. [`dispatch::dispatch`] does the same
+//! with statically dispatched handlers.
+//!
+//! ## Mixed ports
+//! [`demux::Demuxer`] splits a port that interleaves SBF with NMEA, RTCM 3
+//! and command replies into those frames, where [`parser::SbfParser`] only
+//! keeps the SBF.
+//!
+//! ## parallel
+//! The `parallel` feature adds [`parallel::ParallelDecoder`], which
+//! memory-maps a log file and decodes it on every core with rayon.
+//!
+//! ## embedded-io
+//! The `embedded-io` and `embedded-io-async` features add readers over
+//! [`embedded-io`](https://docs.rs/embedded-io) sources in [`embedded`], for
+//! UARTs and embassy sockets on targets without `std`.
+//!
+//! ## tokio
+//! The `tokio` feature adds [`codec::SbfCodec`] for `tokio_util` framed
+//! streams and sinks, and [`codec::recv_datagrams`] for UDP sockets.
+//!
+//! ## Without a heap
+//! Only framing is allocation-free. [`parser::FixedSbfParser`] buffers input
+//! in a fixed-size array, and [`parser::SliceBuffer`] lets an
+//! [`parser::SbfParser`] buffer in memory you provide, so finding and
+//! CRC-checking blocks never allocates. Decoding a block into [`Messages`]
+//! still does: binrw requires `alloc`, and messages keep their sub-blocks and
+//! variable-length fields in `Vec`s, so the crate needs `alloc` and a global
+//! allocator even with these parsers. [`view::parse_ref`] reads a block's
+//! fields in place without decoding it.
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![cfg_attr(not(feature = "std"), no_std)]
@@ -12,17 +73,48 @@ use binrw::{binrw, BinRead, BinWrite};
 
 extern crate alloc;
 
+pub mod demux;
+pub mod dispatch;
+pub mod encoder;
+pub mod epoch;
 pub mod messages;
+pub mod observation;
 pub mod parser;
+pub mod satellite;
+pub mod signal;
+pub mod time;
+pub mod view;
 
 #[cfg(feature = "std")]
 #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
 pub mod reader;
 
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod index;
+
+#[cfg(feature = "parallel")]
+#[cfg_attr(docsrs, doc(cfg(feature = "parallel")))]
+pub mod parallel;
+
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub mod codec;
+
+#[cfg(any(feature = "embedded-io", feature = "embedded-io-async"))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(feature = "embedded-io", feature = "embedded-io-async")))
+)]
+pub mod embedded;
+
 mod binrw_util;
+mod validate;
 
 #[cfg(all(test, feature = "std"))]
 mod mega_test;
+#[cfg(test)]
+mod test_fixtures;
 
 // Re-export all message types at crate level
 pub use messages::{
@@ -45,13 +137,23 @@ pub use messages::{
     ImuSetup, Meas3Doppler, Meas3Ranges, MeasEpoch, MeasEpochChannelType1, MeasEpochChannelType2,
     MeasExtra, MeasExtraChannelSub, NavCart, PVTCartesian, PVTGeodetic, PosCart, PosCovCartesian,
     PosCovGeodetic, PvtError, PvtMode, PvtModeFlags, QualityInd, QualityIndicator, RFBand,
-    RFStatus, RaimIntegrity, ReceiverSetup, ReceiverStatus, ReceiverTime, RiseSet, RxError, RxMessage,
-    RxState, SatInfo, SatVisibility, VectorInfoCart, VectorInfoGeod, VelCovCartesian,
-    VelCovGeodetic, VelSensorSetup, WACorrFlags, XPPSOffset,
+    RFStatus, RaimIntegrity, RawBlock, ReceiverSetup, ReceiverStatus, ReceiverTime, RiseSet,
+    RxError, RxMessage, RxState, SatInfo, SatVisibility, VectorInfoCart, VectorInfoGeod,
+    VelCovCartesian, VelCovGeodetic, VelSensorSetup, WACorrFlags, XPPSOffset,
 };
 
+// Re-export the block encoder error
+pub use encoder::EncodeError;
+
 // Re-export datagram parser
-pub use parser::{parse_datagram, DatagramError, MAX_UDP_PAYLOAD};
+pub use parser::{
+    parse_datagram, parse_datagram_filtered, parse_datagram_with, parse_datagrams, BlockFilter,
+    DatagramError, Datagrams, Framed, ParseOptions, ParserStats, SbfEvent, ValidationError,
+    MAX_UDP_PAYLOAD,
+};
+
+// Re-export the borrowed block views
+pub use view::{parse_ref, MessageRef};
 
 #[binrw]
 #[derive(Debug)]
@@ -60,8 +162,8 @@ struct Id {
 }
 
 impl Id {
-    fn message_type(&self) -> MessageKind {
-        MessageKind::from(self.block_number())
+    fn message_type(&self) -> Option<BlockId> {
+        BlockId::from_number(self.block_number())
     }
 
     fn block_number(&self) -> u16 {
@@ -69,9 +171,9 @@ impl Id {
         self.bytes & 0x1FFF
     }
 
-    fn _block_rev_number(&self) -> u16 {
+    pub(crate) fn revision(&self) -> u8 {
         // NOTE: Bits 13-15 are the Block Revision Number
-        self.bytes & 0xE000
+        (self.bytes >> 13) as u8
     }
 }
 
@@ -128,81 +230,244 @@ pub(crate) trait NestedHeader {
     fn nested_count(&self) -> usize;
 }
 
-/// One first-level sub-block of a two-level SBF block: a fixed header padded to
-/// SB1Length followed by the nested second-level sub-blocks, each padded to
-/// SB2Length.
-#[binrw]
-#[derive(Clone, Debug)]
-#[brw(import(sb1_len: usize, sb2_len: usize))]
-pub(crate) struct NestedBlock<H, T>
-where
-    H: 'static,
-    T: 'static,
-    for<'a> H: BinRead<Args<'a> = ()> + BinWrite<Args<'a> = ()> + NestedHeader + Clone,
-    for<'a> T: BinRead<Args<'a> = ()> + BinWrite<Args<'a> = ()> + Clone,
-{
-    #[brw(pad_size_to = sb1_len)]
-    pub header: H,
-    #[br(args { count: header.nested_count(), inner: (sb2_len,) })]
-    #[bw(args_raw = (sb2_len,))]
-    pub items: alloc::vec::Vec<SubBlock<T>>,
+/// One first-level sub-block of a two-level SBF block, kept flat in the public
+/// type: a fixed header padded to SB1Length followed by the nested second-level
+/// sub-blocks, each padded to SB2Length.
+pub(crate) trait NestedBlock: Sized {
+    type Header: 'static
+        + for<'a> BinRead<Args<'a> = ()>
+        + for<'a> BinWrite<Args<'a> = ()>
+        + NestedHeader;
+    type Item: 'static + for<'a> BinRead<Args<'a> = ()> + for<'a> BinWrite<Args<'a> = ()>;
+
+    fn from_parts(header: Self::Header, items: alloc::vec::Vec<Self::Item>) -> Self;
+    fn header(&self) -> Self::Header;
+    fn items(&self) -> &[Self::Item];
+}
+
+/// A message type decoded from a single SBF block number.
+pub trait SbfBlock: Sized {
+    /// The block number, bits 0-12 of the block ID.
+    const BLOCK_NUMBER: u16;
+    /// The block name used in the SBF reference guide, e.g. `"PVTGeodetic"`.
+    const NAME: &'static str;
+
+    /// Take the message out of `msg` if it is this block, or give `msg`
+    /// back. See also [`Messages::downcast`].
+    // Giving the message back is the point, large as it is.
+    #[allow(clippy::result_large_err)]
+    fn from_message(msg: Messages) -> Result<Self, Messages>;
+
+    /// Borrow the message in `msg` if it is this block. See also
+    /// [`Messages::downcast_ref`].
+    fn from_message_ref(msg: &Messages) -> Option<&Self>;
+}
+
+/// Every block in [`BlockId`] order, which is by block number.
+pub fn supported_blocks() -> impl Iterator<Item = BlockId> {
+    BlockId::ALL.iter().copied()
 }
 
 macro_rules! define_messages {
     ($($variant:ident => $code:literal,)+) => {
-        /// Typed enum that can be used to determine the type of message
-        /// received.
-        #[derive(Debug)]
-        enum MessageKind {
+        $(
+            impl SbfBlock for $variant {
+                const BLOCK_NUMBER: u16 = $code;
+                const NAME: &'static str = stringify!($variant);
+
+                fn from_message(msg: Messages) -> Result<Self, Messages> {
+                    match msg {
+                        Messages::$variant(m) => Ok(m),
+                        other => Err(other),
+                    }
+                }
+
+                fn from_message_ref(msg: &Messages) -> Option<&Self> {
+                    match msg {
+                        Messages::$variant(m) => Some(m),
+                        _ => None,
+                    }
+                }
+            }
+
+            impl From<$variant> for Messages {
+                fn from(msg: $variant) -> Self {
+                    Messages::$variant(msg)
+                }
+            }
+
+            impl TryFrom<Messages> for $variant {
+                /// The message, if it is a different block.
+                type Error = Messages;
+
+                fn try_from(msg: Messages) -> Result<Self, Messages> {
+                    <$variant as SbfBlock>::from_message(msg)
+                }
+            }
+        )+
+
+        /// The blocks this crate decodes, one per [`Messages`] variant other
+        /// than [`Messages::RawBlock`].
+        ///
+        /// ```
+        /// use libsbf::BlockId;
+        ///
+        /// let id = BlockId::from_name("PVTGeodetic").unwrap();
+        /// assert_eq!(id.number(), 4007);
+        /// assert_eq!(BlockId::from_number(4007), Some(id));
+        /// ```
+        #[allow(clippy::upper_case_acronyms)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum BlockId {
             $( $variant, )+
-            Unsupported,
         }
 
-        impl From<u16> for MessageKind {
-            fn from(block_number: u16) -> Self {
+        impl BlockId {
+            const ALL: &'static [BlockId] = &[ $( BlockId::$variant, )+ ];
+
+            /// The block number, bits 0-12 of the block ID.
+            pub const fn number(self) -> u16 {
+                match self {
+                    $( BlockId::$variant => $code, )+
+                }
+            }
+
+            /// The block name used in the SBF reference guide.
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $( BlockId::$variant => stringify!($variant), )+
+                }
+            }
+
+            /// The block with number `block_number`, if this crate decodes it.
+            pub const fn from_number(block_number: u16) -> Option<Self> {
                 match block_number {
-                    $( $code => MessageKind::$variant, )+
-                    _ => MessageKind::Unsupported,
+                    $( $code => Some(BlockId::$variant), )+
+                    _ => None,
+                }
+            }
+
+            /// The block named `name`, as spelled in the SBF reference guide.
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    $( stringify!($variant) => Some(BlockId::$variant), )+
+                    _ => None,
                 }
             }
         }
 
         /// Detailed enum that holds the associated payload.
+        #[allow(clippy::upper_case_acronyms)]
         #[derive(Clone, Debug)]
         pub enum Messages {
             $( $variant($variant), )+
-            Unsupported(u16),
+            /// A block this crate does not decode, kept as raw bytes.
+            RawBlock(RawBlock),
         }
 
         impl Messages {
+            /// The block name, or `"RawBlock"` for blocks kept undecoded.
             pub fn type_name(&self) -> &'static str {
                 match self {
                     $( Messages::$variant(_) => stringify!($variant), )+
-                    Messages::Unsupported(_) => "Unsupported",
+                    Messages::RawBlock(_) => "RawBlock",
                 }
             }
 
             pub fn tow(&self) -> Option<u32> {
                 match self {
                     $( Messages::$variant(m) => m.tow, )+
-                    Messages::Unsupported(_) => None,
+                    Messages::RawBlock(m) => m.tow,
                 }
             }
 
             pub fn wnc(&self) -> Option<u16> {
                 match self {
                     $( Messages::$variant(m) => m.wnc, )+
-                    Messages::Unsupported(_) => None,
+                    Messages::RawBlock(m) => m.wnc,
                 }
             }
 
-            /// Read a supported block's payload into its `Messages` variant. The
-            /// caller resolves `Unsupported` before dispatching here.
-            pub(crate) fn parse_body(kind: MessageKind, payload: &[u8]) -> binrw::BinResult<Self> {
+            /// The block number, bits 0-12 of the block ID. Raw blocks keep
+            /// the number they were read with.
+            pub fn block_number(&self) -> u16 {
+                match self {
+                    $( Messages::$variant(_) => $code, )+
+                    Messages::RawBlock(m) => m.id,
+                }
+            }
+
+            /// The decoded block, or None for [`Messages::RawBlock`].
+            pub fn block(&self) -> Option<BlockId> {
+                match self {
+                    $( Messages::$variant(_) => Some(BlockId::$variant), )+
+                    Messages::RawBlock(_) => None,
+                }
+            }
+
+            /// Borrow the message if it is a `T`.
+            ///
+            /// ```
+            /// use libsbf::{Messages, PVTGeodetic};
+            ///
+            /// fn height(msg: &Messages) -> Option<f64> {
+            ///     msg.downcast_ref::<PVTGeodetic>()?.height
+            /// }
+            /// ```
+            pub fn downcast_ref<T: SbfBlock>(&self) -> Option<&T> {
+                T::from_message_ref(self)
+            }
+
+            /// Take the message out if it is a `T`, or give it back.
+            #[allow(clippy::result_large_err)]
+            pub fn downcast<T: SbfBlock>(self) -> Result<T, Messages> {
+                T::from_message(self)
+            }
+
+            /// Block revision number (bits 13-15 of the block ID) the message
+            /// was decoded from and is encoded with. Only some blocks lay out
+            /// their fields by revision, see the crate docs.
+            pub fn revision(&self) -> u8 {
+                match self {
+                    $( Messages::$variant(m) => m.revision, )+
+                    Messages::RawBlock(m) => m.revision,
+                }
+            }
+
+            /// Block ID written when encoding: the block number with the
+            /// revision in bits 13-15.
+            pub(crate) fn block_id(&self) -> u16 {
+                self.block_number() | (u16::from(self.revision()) << 13)
+            }
+
+            /// Write the block body (everything after the header) with the
+            /// same binrw layout `parse_body` reads.
+            pub(crate) fn write_body<W>(&self, writer: &mut W) -> Result<(), encoder::EncodeError>
+            where
+                W: binrw::io::Write + binrw::io::Seek,
+            {
+                match self {
+                    $( Messages::$variant(m) => m.write_le(writer)?, )+
+                    Messages::RawBlock(m) => m.write_le(writer)?,
+                }
+                Ok(())
+            }
+
+            /// Read a supported block's payload into its `Messages` variant,
+            /// laid out for the given block revision. The caller reads
+            /// unsupported blocks with `parse_raw` instead.
+            pub(crate) fn parse_body(
+                kind: BlockId,
+                revision: u8,
+                payload: &[u8],
+            ) -> binrw::BinResult<Self> {
                 let mut cursor = binrw::io::Cursor::new(payload);
                 Ok(match kind {
-                    $( MessageKind::$variant => Self::$variant($variant::read_le(&mut cursor)?), )+
-                    MessageKind::Unsupported => unreachable!("Unsupported is surfaced before parse_body"),
+                    $(
+                        BlockId::$variant => {
+                            Self::$variant($variant::read_le_args(&mut cursor, (revision,))?)
+                        }
+                    )+
                 })
             }
         }
@@ -268,6 +533,16 @@ define_messages!(
     EndOfAtt => 5943,
 );
 
+impl Messages {
+    /// Keep a block's payload undecoded as a `RawBlock`.
+    pub(crate) fn parse_raw(block_id: &Id, payload: &[u8]) -> binrw::BinResult<Self> {
+        let mut raw =
+            RawBlock::read_le_args(&mut binrw::io::Cursor::new(payload), (block_id.revision(),))?;
+        raw.id = block_id.block_number();
+        Ok(Messages::RawBlock(raw))
+    }
+}
+
 pub fn is_sync(bytes: &[u8; 2]) -> bool {
     bytes == b"$@"
 }
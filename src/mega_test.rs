@@ -5,7 +5,9 @@ mod tests {
         EndOfAtt, EndOfPVT, ExtEvent, ExtEventINSNavCart, ExtEventINSNavGeod, ExtSensorMeas,
         INSNavCart, INSNavGeod, MeasEpoch, MeasExtra, Messages, NavCart, PVTCartesian, PVTGeodetic,
         PosCart, QualityInd, RFStatus, ReceiverStatus, ReceiverTime, RxMessage, SatVisibility,
-        reader::SbfReader,
+        BlockFilter, MessageRef, ParseOptions, SbfEvent, epoch::{Epoch, EpochAssembler},
+        index::SbfIndex, satellite::Constellation,
+        parser::SbfParser, reader::SbfReader,
     };
     use binrw::{io::Cursor, BinRead, BinWrite};
     use std::collections::HashMap;
@@ -282,11 +284,11 @@ mod tests {
     /// per SBF 4.1.5, so it is not reproduced.
     #[test]
     fn test_mega_file_roundtrip() {
-        fn round_trip<T>(body: &[u8], block: u16, count: &mut usize)
+        fn round_trip<T>(body: &[u8], block: u16, revision: u8, count: &mut usize)
         where
-            for<'a> T: BinRead<Args<'a> = ()> + BinWrite<Args<'a> = ()>,
+            for<'a> T: BinRead<Args<'a> = (u8,)> + BinWrite<Args<'a> = ()>,
         {
-            let value = T::read_le(&mut Cursor::new(body))
+            let value = T::read_le_args(&mut Cursor::new(body), (revision,))
                 .unwrap_or_else(|e| panic!("block {block} failed to read: {e:?}"));
             let mut out = Vec::new();
             value
@@ -319,34 +321,35 @@ mod tests {
                 continue;
             }
             let block = ident & 0x1FFF;
+            let revision = (ident >> 13) as u8;
             let body = &data[i + 8..i + length];
             match block {
-                4000 => round_trip::<MeasExtra>(body, block, &mut round_tripped),
-                4006 => round_trip::<PVTCartesian>(body, block, &mut round_tripped),
-                4007 => round_trip::<PVTGeodetic>(body, block, &mut round_tripped),
-                4012 => round_trip::<SatVisibility>(body, block, &mut round_tripped),
-                4013 => round_trip::<ChannelStatus>(body, block, &mut round_tripped),
-                4014 => round_trip::<ReceiverStatus>(body, block, &mut round_tripped),
-                4027 => round_trip::<MeasEpoch>(body, block, &mut round_tripped),
-                4028 => round_trip::<BaseVectorGeod>(body, block, &mut round_tripped),
-                4043 => round_trip::<BaseVectorCart>(body, block, &mut round_tripped),
-                4044 => round_trip::<PosCart>(body, block, &mut round_tripped),
-                4050 => round_trip::<ExtSensorMeas>(body, block, &mut round_tripped),
-                4059 => round_trip::<DiskStatus>(body, block, &mut round_tripped),
-                4082 => round_trip::<QualityInd>(body, block, &mut round_tripped),
-                4092 => round_trip::<RFStatus>(body, block, &mut round_tripped),
-                4103 => round_trip::<RxMessage>(body, block, &mut round_tripped),
-                4225 => round_trip::<INSNavCart>(body, block, &mut round_tripped),
-                4226 => round_trip::<INSNavGeod>(body, block, &mut round_tripped),
-                4229 => round_trip::<ExtEventINSNavCart>(body, block, &mut round_tripped),
-                4230 => round_trip::<ExtEventINSNavGeod>(body, block, &mut round_tripped),
-                4272 => round_trip::<NavCart>(body, block, &mut round_tripped),
-                5914 => round_trip::<ReceiverTime>(body, block, &mut round_tripped),
-                5921 => round_trip::<EndOfPVT>(body, block, &mut round_tripped),
-                5924 => round_trip::<ExtEvent>(body, block, &mut round_tripped),
-                5936 => round_trip::<Comment>(body, block, &mut round_tripped),
-                5942 => round_trip::<AuxAntPositions>(body, block, &mut round_tripped),
-                5943 => round_trip::<EndOfAtt>(body, block, &mut round_tripped),
+                4000 => round_trip::<MeasExtra>(body, block, revision, &mut round_tripped),
+                4006 => round_trip::<PVTCartesian>(body, block, revision, &mut round_tripped),
+                4007 => round_trip::<PVTGeodetic>(body, block, revision, &mut round_tripped),
+                4012 => round_trip::<SatVisibility>(body, block, revision, &mut round_tripped),
+                4013 => round_trip::<ChannelStatus>(body, block, revision, &mut round_tripped),
+                4014 => round_trip::<ReceiverStatus>(body, block, revision, &mut round_tripped),
+                4027 => round_trip::<MeasEpoch>(body, block, revision, &mut round_tripped),
+                4028 => round_trip::<BaseVectorGeod>(body, block, revision, &mut round_tripped),
+                4043 => round_trip::<BaseVectorCart>(body, block, revision, &mut round_tripped),
+                4044 => round_trip::<PosCart>(body, block, revision, &mut round_tripped),
+                4050 => round_trip::<ExtSensorMeas>(body, block, revision, &mut round_tripped),
+                4059 => round_trip::<DiskStatus>(body, block, revision, &mut round_tripped),
+                4082 => round_trip::<QualityInd>(body, block, revision, &mut round_tripped),
+                4092 => round_trip::<RFStatus>(body, block, revision, &mut round_tripped),
+                4103 => round_trip::<RxMessage>(body, block, revision, &mut round_tripped),
+                4225 => round_trip::<INSNavCart>(body, block, revision, &mut round_tripped),
+                4226 => round_trip::<INSNavGeod>(body, block, revision, &mut round_tripped),
+                4229 => round_trip::<ExtEventINSNavCart>(body, block, revision, &mut round_tripped),
+                4230 => round_trip::<ExtEventINSNavGeod>(body, block, revision, &mut round_tripped),
+                4272 => round_trip::<NavCart>(body, block, revision, &mut round_tripped),
+                5914 => round_trip::<ReceiverTime>(body, block, revision, &mut round_tripped),
+                5921 => round_trip::<EndOfPVT>(body, block, revision, &mut round_tripped),
+                5924 => round_trip::<ExtEvent>(body, block, revision, &mut round_tripped),
+                5936 => round_trip::<Comment>(body, block, revision, &mut round_tripped),
+                5942 => round_trip::<AuxAntPositions>(body, block, revision, &mut round_tripped),
+                5943 => round_trip::<EndOfAtt>(body, block, revision, &mut round_tripped),
                 _ => {}
             }
             *present.entry(block).or_insert(0) += 1;
@@ -357,4 +360,303 @@ mod tests {
         assert!(present.contains_key(&4013), "ChannelStatus should be present");
         assert!(present.contains_key(&4027), "MeasEpoch should be present");
     }
+
+    /// Encodes every decoded message back into a framed block and checks it
+    /// parses as a datagram to the same message.
+    #[test]
+    fn test_mega_file_encode() {
+        let input_stream =
+            File::open("test-files/mega_test.sbf").expect("Failed to open mega_test.sbf");
+
+        let mut encoded = 0usize;
+        for msg in SbfReader::new(input_stream).flatten() {
+            let bytes = msg
+                .encode()
+                .unwrap_or_else(|e| panic!("{} failed to encode: {e:?}", msg.type_name()));
+            assert_eq!(bytes.len() % 4, 0, "{} is not padded to 4", msg.type_name());
+            let reparsed = crate::parse_datagram(&bytes)
+                .unwrap_or_else(|e| panic!("{} did not parse back: {e:?}", msg.type_name()));
+            assert_eq!(format!("{reparsed:?}"), format!("{msg:?}"));
+            encoded += 1;
+        }
+
+        assert!(encoded > 0, "no messages were encoded");
+    }
+
+    /// The mega file is clean, so the event stream holds nothing but messages
+    /// and the reader's counters agree with it.
+    #[test]
+    fn test_mega_file_events() {
+        let input_stream =
+            File::open("test-files/mega_test.sbf").expect("Failed to open mega_test.sbf");
+
+        let mut events = SbfReader::new(input_stream).events();
+        let mut messages = 0u64;
+        for event in &mut events {
+            match event.expect("read mega_test.sbf") {
+                SbfEvent::Message(_) => messages += 1,
+                other => panic!("unexpected event in mega_test.sbf: {other:?}"),
+            }
+        }
+
+        let stats = events.stats();
+        assert_eq!(stats.messages, messages);
+        assert_eq!(stats.bytes_skipped, 0);
+        assert_eq!(stats.crc_errors + stats.header_errors + stats.payload_errors, 0);
+    }
+
+    /// The borrowed views read the same values as the decoded messages.
+    #[test]
+    fn test_mega_file_views() {
+        let input_stream =
+            File::open("test-files/mega_test.sbf").expect("Failed to open mega_test.sbf");
+
+        let mut checked = 0usize;
+        for msg in SbfReader::new(input_stream).flatten() {
+            let bytes = msg.encode().expect("encode");
+            match (crate::parse_ref(&bytes).expect("parse_ref"), &msg) {
+                (MessageRef::MeasEpoch(view), Messages::MeasEpoch(me)) => {
+                    assert_eq!(view.tow(), me.tow);
+                    assert_eq!(view.channels().len(), me.channel_type1.len());
+                    for (ch, decoded) in view.channels().zip(me.channel_type1.iter()) {
+                        assert_eq!(ch.svid(), decoded.svid);
+                        assert_eq!(ch.doppler(), decoded.doppler);
+                        assert_eq!(ch.lock_time(), decoded.lock_time);
+                        assert_eq!(ch.signals().len(), decoded.channel_type2.len());
+                        for (sig, t2) in ch.signals().zip(decoded.channel_type2.iter()) {
+                            assert_eq!(sig.type_field(), t2.type_field);
+                            assert_eq!(sig.cn0(), t2.cn0);
+                            assert_eq!(sig.doppler_offset_lsb(), t2.doppler_offset_lsb);
+                        }
+                    }
+                    checked += 1;
+                }
+                (MessageRef::ExtSensorMeas(view), Messages::ExtSensorMeas(esm)) => {
+                    assert_eq!(view.sets().len(), esm.ext_sensor_meas_set.len());
+                    for (set, decoded) in view.sets().zip(esm.ext_sensor_meas_set.iter()) {
+                        assert_eq!(set.type_(), decoded.type_);
+                        assert_eq!(
+                            format!("{:?}", set.decode().expect("decode set")),
+                            format!("{decoded:?}")
+                        );
+                    }
+                    checked += 1;
+                }
+                (MessageRef::Other(block), msg) => {
+                    assert_eq!(block.tow(), msg.tow());
+                    assert_eq!(block.revision(), msg.revision());
+                }
+                (view, msg) => panic!("{} got the wrong view: {view:?}", msg.type_name()),
+            }
+        }
+
+        assert!(checked > 0, "no MeasEpoch or ExtSensorMeas blocks were checked");
+    }
+
+    /// A filtered reader yields exactly the selected blocks and counts the rest.
+    #[test]
+    fn test_mega_file_filter() {
+        let all: Vec<Messages> = SbfReader::new(
+            File::open("test-files/mega_test.sbf").expect("Failed to open mega_test.sbf"),
+        )
+        .flatten()
+        .collect();
+        let pvt_count = all
+            .iter()
+            .filter(|m| matches!(m, Messages::PVTGeodetic(_)))
+            .count();
+        assert!(pvt_count > 0);
+
+        let mut reader = SbfReader::new(
+            File::open("test-files/mega_test.sbf").expect("Failed to open mega_test.sbf"),
+        )
+        .with_filter(BlockFilter::none().allow_type::<PVTGeodetic>());
+        let filtered: Vec<Messages> = (&mut reader).flatten().collect();
+        assert_eq!(filtered.len(), pvt_count);
+        assert!(filtered.iter().all(|m| matches!(m, Messages::PVTGeodetic(_))));
+        assert_eq!(reader.stats().blocks_filtered, (all.len() - pvt_count) as u64);
+    }
+
+    /// A typed reader yields the same blocks as downcasting every message.
+    #[test]
+    fn test_mega_file_only() {
+        let all: Vec<Messages> = SbfReader::new(
+            File::open("test-files/mega_test.sbf").expect("Failed to open mega_test.sbf"),
+        )
+        .flatten()
+        .collect();
+        let expected: Vec<Option<u32>> = all
+            .iter()
+            .filter_map(|m| m.downcast_ref::<PVTGeodetic>())
+            .map(|pvt| pvt.tow)
+            .collect();
+        assert!(!expected.is_empty());
+
+        let only: Vec<PVTGeodetic> = SbfReader::new(
+            File::open("test-files/mega_test.sbf").expect("Failed to open mega_test.sbf"),
+        )
+        .only::<PVTGeodetic>()
+        .collect::<Result<_, _>>()
+        .unwrap();
+        assert_eq!(only.iter().map(|pvt| pvt.tow).collect::<Vec<_>>(), expected);
+
+        let msg = Messages::from(only[0].clone());
+        assert!(msg.downcast_ref::<PVTCartesian>().is_none());
+        let msg = PVTCartesian::try_from(msg).unwrap_err();
+        assert_eq!(PVTGeodetic::try_from(msg).unwrap().tow, expected[0]);
+    }
+
+    /// Every PVT, measurement and attitude block ends up in an epoch.
+    #[test]
+    fn test_mega_file_epochs() {
+        let mut epochs = EpochAssembler::new();
+        let mut grouped = 0;
+        for msg in SbfReader::new(
+            File::open("test-files/mega_test.sbf").expect("Failed to open mega_test.sbf"),
+        ) {
+            let msg = msg.unwrap();
+            let time = msg.time();
+            if epochs.push(msg).is_none() {
+                assert!(time.is_some());
+                grouped += 1;
+            }
+        }
+        epochs.flush();
+
+        let epochs: Vec<Epoch> = std::iter::from_fn(|| epochs.next_epoch()).collect();
+        assert!(grouped > 0);
+        // The file has EndOfPVT, EndOfMeas and EndOfAtt enabled.
+        assert_eq!(epochs.len(), 4);
+        assert!(epochs.iter().all(Epoch::is_complete));
+        assert!(epochs.iter().any(|e| matches!(
+            e,
+            Epoch::Pvt(pvt) if pvt.complete && pvt.pvt_geodetic.is_some()
+        )));
+    }
+
+    /// Every satellite the receiver reports has a valid SVID.
+    #[test]
+    fn test_mega_file_satellites() {
+        let mut satellites = 0;
+        for msg in SbfReader::new(
+            File::open("test-files/mega_test.sbf").expect("Failed to open mega_test.sbf"),
+        ) {
+            match msg.unwrap() {
+                Messages::MeasEpoch(m) => {
+                    for ch in m.channel_type1.iter() {
+                        assert!(ch.satellite().is_some(), "SVID {}", ch.svid);
+                        satellites += 1;
+                    }
+                }
+                Messages::SatVisibility(m) => {
+                    for sat in m.satellites.iter() {
+                        assert!(sat.satellite().is_some(), "SVID {}", sat.svid);
+                        satellites += 1;
+                    }
+                }
+                Messages::GPSNav(m) => {
+                    let sat = m.satellite().unwrap();
+                    assert_eq!(sat.constellation(), Constellation::Gps);
+                    satellites += 1;
+                }
+                _ => {}
+            }
+        }
+        assert!(satellites > 0);
+    }
+
+    /// Framed offsets point back at each block in the file.
+    #[test]
+    fn test_mega_file_framed() {
+        let data = fs::read("test-files/mega_test.sbf").expect("Failed to read mega_test.sbf");
+
+        let mut next_offset = 0u64;
+        for framed in SbfReader::new(&data[..]).framed() {
+            let framed = framed.expect("read mega_test.sbf");
+            assert_eq!(framed.offset, next_offset);
+            assert!(framed.host_time.is_some());
+            let start = framed.offset as usize;
+            let block = &data[start..start + framed.len];
+            assert_eq!(block[..2], *b"$@");
+            let id = u16::from_le_bytes([block[4], block[5]]);
+            assert_eq!(framed.block_id, id & 0x1FFF);
+            assert_eq!(framed.revision, (id >> 13) as u8);
+            assert_eq!(framed.msg.revision(), framed.revision);
+            next_offset += framed.len as u64;
+        }
+        assert_eq!(next_offset, data.len() as u64);
+    }
+
+    /// The index finds every block the reader does, and seeking by time
+    /// resumes reading at the right block.
+    #[test]
+    fn test_mega_file_index() {
+        let data = fs::read("test-files/mega_test.sbf").expect("Failed to read mega_test.sbf");
+        let index = SbfIndex::build(&data[..]).expect("index mega_test.sbf");
+        let framed: Vec<_> = SbfReader::new(&data[..]).framed().flatten().collect();
+        assert_eq!(index.entries().len(), framed.len());
+        for (entry, framed) in index.entries().iter().zip(&framed) {
+            assert_eq!(entry.offset, framed.offset);
+            assert_eq!(usize::from(entry.len), framed.len);
+            assert_eq!(entry.block_id, framed.block_id);
+            assert_eq!(entry.tow, framed.msg.tow());
+            assert_eq!(entry.wnc, framed.msg.wnc());
+        }
+
+        let middle = index.entries()[index.entries().len() / 2];
+        let (wnc, tow) = middle.time().expect("time stamped block");
+        let position = index.position_at(wnc, tow).unwrap();
+        let mut reader = index
+            .seek_to_time(std::io::Cursor::new(&data), wnc, tow)
+            .expect("seek");
+        let first = reader.next().expect("a block").expect("read");
+        assert_eq!(first.tow(), index.entries()[position].tow);
+        assert_eq!(reader.count() + 1, index.entries().len() - position);
+    }
+
+    /// Decoding in parallel chunks gives the same blocks as the reader.
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_mega_file_parallel() {
+        use crate::parallel::ParallelDecoder;
+
+        let path = "test-files/mega_test.sbf";
+        let framed: Vec<_> = SbfReader::new(File::open(path).expect("open mega_test.sbf"))
+            .framed()
+            .flatten()
+            .collect();
+        let decoded = ParallelDecoder::new()
+            .with_chunk_size(4096)
+            .decode_file(path)
+            .expect("decode mega_test.sbf");
+        assert_eq!(decoded.frames.len(), framed.len());
+        assert_eq!(decoded.stats.messages, framed.len() as u64);
+        for (parallel, sequential) in decoded.frames.iter().zip(&framed) {
+            assert_eq!(parallel.offset, sequential.offset);
+            assert_eq!(
+                format!("{:?}", parallel.msg),
+                format!("{:?}", sequential.msg)
+            );
+        }
+    }
+
+    /// Every block's counts, sub-block lengths and padding agree with its
+    /// length. The receiver does fill a few reserved fields.
+    #[test]
+    fn test_mega_file_strict() {
+        let data = fs::read("test-files/mega_test.sbf").expect("Failed to read mega_test.sbf");
+        let mut parser = SbfParser::new().with_options(ParseOptions { strict: true });
+        let mut input = &data[..];
+        while let Some(event) = parser.consume_event(input) {
+            input = &[];
+            if let SbfEvent::ValidationError { error, .. } = event {
+                assert_eq!(error.field, "reserved", "{error:?}");
+                // GPSNav and GEONav after the PRN, and DiskStatus.
+                assert!(matches!(error.block_id, 5891 | 5896 | 4059), "{error:?}");
+            }
+        }
+        let stats = parser.stats();
+        let lenient = SbfReader::new(&data[..]).count() as u64;
+        assert_eq!(stats.messages + stats.validation_errors, lenient);
+    }
 }
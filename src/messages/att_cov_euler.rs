@@ -6,6 +6,7 @@ use super::att_euler::BaselineError;
 // AttCovEuler Block 5939
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct AttCovEuler {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -33,6 +34,10 @@ pub struct AttCovEuler {
     #[br(map = binrw_util::map_f4)]
     #[bw(map = binrw_util::unmap_f4)]
     pub cov_pitch_roll: Option<f32>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 impl AttCovEuler {
@@ -69,6 +69,7 @@ impl From<u8> for BaselineError {
 // Attitude Euler Block 5938
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct AttEuler {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -81,7 +82,7 @@ pub struct AttEuler {
     pub nrsv: Option<u8>,
     error_raw: u8,
     mode_raw: u16,
-    _reserved: u16,
+    pub(crate) _reserved: u16,
 
     #[br(map = binrw_util::map_f4)]
     #[bw(map = binrw_util::unmap_f4)]
@@ -102,6 +103,10 @@ pub struct AttEuler {
     #[br(map = binrw_util::map_f4)]
     #[bw(map = binrw_util::unmap_f4)]
     pub heading_dot: Option<f32>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 impl AttEuler {
@@ -6,6 +6,7 @@ use super::pvt_geodetic::{PvtError, PvtMode, PvtModeFlags};
 // BaseVectorGeod Block 4028
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct BaseVectorGeod {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -15,9 +16,13 @@ pub struct BaseVectorGeod {
     pub wnc: Option<u16>,
     pub n: u8,
     pub sb_length: u8,
-    #[br(args { count: usize::from(n), inner: (usize::from(sb_length),) }, map = binrw_util::unwrap_subblocks)]
-    #[bw(args_raw = (usize::from(*sb_length),), map = binrw_util::wrap_subblocks)]
+    #[br(parse_with = binrw_util::read_subblocks, args(usize::from(n), usize::from(sb_length)))]
+    #[bw(write_with = binrw_util::write_subblocks, args(usize::from(*sb_length)))]
     pub vectors: Vec<VectorInfoGeod>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 // VectorInfoGeod sub-block
@@ -5,6 +5,7 @@ use binrw::binrw;
 // BDSIon Block 4120
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct BDSIon {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -24,4 +25,8 @@ pub struct BDSIon {
     pub beta_3: f32,
     #[br(parse_with = binrw::helpers::until_eof)]
     pub padding: Vec<u8>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
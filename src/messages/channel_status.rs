@@ -1,5 +1,5 @@
 use crate::binrw_util;
-use crate::{NestedBlock, NestedHeader, SubBlock};
+use crate::{NestedBlock, NestedHeader};
 use alloc::vec::Vec;
 use binrw::binrw;
 use super::sat_visibility::RiseSet;
@@ -7,6 +7,7 @@ use super::sat_visibility::RiseSet;
 // ChannelStatus Block 4013
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct ChannelStatus {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -18,18 +19,22 @@ pub struct ChannelStatus {
     pub sb1_length: u8,
     pub sb2_length: u8,
     pub reserved: [u8; 3],
-    #[br(args { count: usize::from(n), inner: (usize::from(sb1_length), usize::from(sb2_length)) },
-         map = |v: Vec<NestedBlock<ChannelSatInfoHeader, ChannelStateInfo>>| v.into_iter().map(ChannelSatInfo::from).collect())]
-    #[bw(args_raw = (usize::from(*sb1_length), usize::from(*sb2_length)),
-         map = |v: &Vec<ChannelSatInfo>| v.iter().cloned().map(NestedBlock::from).collect::<Vec<NestedBlock<ChannelSatInfoHeader, ChannelStateInfo>>>())]
+    #[br(parse_with = binrw_util::read_nested_blocks,
+         args(usize::from(n), usize::from(sb1_length), usize::from(sb2_length)))]
+    #[bw(write_with = binrw_util::write_nested_blocks,
+         args(usize::from(*sb1_length), usize::from(*sb2_length)))]
     pub sat_info: Vec<ChannelSatInfo>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 // First-level header of a ChannelSatInfo sub-block. Internal wire type; the flat
-// public ChannelSatInfo is bridged from NestedBlock via From/Into.
+// public ChannelSatInfo is bridged to it through NestedBlock.
 #[binrw]
 #[derive(Clone, Debug)]
-struct ChannelSatInfoHeader {
+pub(crate) struct ChannelSatInfoHeader {
     pub svid: u8,
     /// GLONASS frequency number with an offset of 8, from 1 to 21; reserved otherwise.
     #[br(map = binrw_util::map_u1_zero)]
@@ -71,40 +76,42 @@ pub struct ChannelSatInfo {
     pub state_info: Vec<ChannelStateInfo>,
 }
 
-impl From<NestedBlock<ChannelSatInfoHeader, ChannelStateInfo>> for ChannelSatInfo {
-    fn from(nb: NestedBlock<ChannelSatInfoHeader, ChannelStateInfo>) -> Self {
+impl NestedBlock for ChannelSatInfo {
+    type Header = ChannelSatInfoHeader;
+    type Item = ChannelStateInfo;
+
+    fn from_parts(header: ChannelSatInfoHeader, state_info: Vec<Self::Item>) -> Self {
         ChannelSatInfo {
-            svid: nb.header.svid,
-            freq_nr: nb.header.freq_nr,
-            reserved1: nb.header.reserved1,
-            azimuth_rise_set: nb.header.azimuth_rise_set,
-            health_status: nb.header.health_status,
-            elevation: nb.header.elevation,
-            n2: nb.header.n2,
-            rx_channel: nb.header.rx_channel,
-            reserved2: nb.header.reserved2,
-            state_info: nb.items.into_iter().map(SubBlock::into_inner).collect(),
+            svid: header.svid,
+            freq_nr: header.freq_nr,
+            reserved1: header.reserved1,
+            azimuth_rise_set: header.azimuth_rise_set,
+            health_status: header.health_status,
+            elevation: header.elevation,
+            n2: header.n2,
+            rx_channel: header.rx_channel,
+            reserved2: header.reserved2,
+            state_info,
         }
     }
-}
 
-impl From<ChannelSatInfo> for NestedBlock<ChannelSatInfoHeader, ChannelStateInfo> {
-    fn from(si: ChannelSatInfo) -> Self {
-        NestedBlock {
-            header: ChannelSatInfoHeader {
-                svid: si.svid,
-                freq_nr: si.freq_nr,
-                reserved1: si.reserved1,
-                azimuth_rise_set: si.azimuth_rise_set,
-                health_status: si.health_status,
-                elevation: si.elevation,
-                n2: si.n2,
-                rx_channel: si.rx_channel,
-                reserved2: si.reserved2,
-            },
-            items: si.state_info.into_iter().map(SubBlock::from).collect(),
+    fn header(&self) -> ChannelSatInfoHeader {
+        ChannelSatInfoHeader {
+            svid: self.svid,
+            freq_nr: self.freq_nr,
+            reserved1: self.reserved1,
+            azimuth_rise_set: self.azimuth_rise_set,
+            health_status: self.health_status,
+            elevation: self.elevation,
+            n2: self.n2,
+            rx_channel: self.rx_channel,
+            reserved2: self.reserved2,
         }
     }
+
+    fn items(&self) -> &[Self::Item] {
+        &self.state_info
+    }
 }
 
 impl ChannelSatInfo {
@@ -5,6 +5,7 @@ use binrw::binrw;
 // Commands Block 4015
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct Commands {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -15,4 +16,8 @@ pub struct Commands {
     pub reserved: [u8; 2],
     #[br(parse_with = binrw::helpers::until_eof)]
     pub cmd_data: Vec<u8>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
@@ -5,6 +5,7 @@ use binrw::binrw;
 // Comment Block 5936
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct Comment {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -16,4 +17,8 @@ pub struct Comment {
     pub comment_ln: u16,
     #[br(count = usize::from(comment_ln))]
     pub comment: Vec<u8>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
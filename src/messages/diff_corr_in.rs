@@ -5,6 +5,7 @@ use binrw::binrw;
 // DiffCorrIn Block 5919
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct DiffCorrIn {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -21,6 +22,10 @@ pub struct DiffCorrIn {
     // binrw will read all remaining bytes
     #[br(parse_with = binrw::helpers::until_eof)]
     pub message_data: Vec<u8>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 // Mode constants for clarity
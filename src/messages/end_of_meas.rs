@@ -5,6 +5,7 @@ use binrw::binrw;
 // EndOfMeas Block 5922
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct EndOfMeas {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -14,4 +15,8 @@ pub struct EndOfMeas {
     pub wnc: Option<u16>,
     #[br(parse_with = binrw::helpers::until_eof)]
     pub padding: Vec<u8>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
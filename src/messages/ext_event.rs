@@ -1,10 +1,12 @@
 use crate::binrw_util;
+use crate::encoder::{check_optional, EncodeError};
 use alloc::vec::Vec;
 use binrw::binrw;
 
 // ExtEvent Block 5924
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct ExtEvent {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -20,12 +22,16 @@ pub struct ExtEvent {
     #[br(map = binrw_util::map_f8)]
     #[bw(map = binrw_util::unmap_f8)]
     pub rx_clk_bias: Option<f64>,
-    // Rev 1
-    #[br(map = binrw_util::map_u2)]
-    #[bw(map = binrw_util::unmap_u2)]
+    // Rev 1, None in revision 0 blocks
+    #[br(if(revision >= 1, None), map = binrw_util::map_u2)]
+    #[bw(if(*revision >= 1), map = binrw_util::unmap_u2)]
     pub pvt_age: Option<u16>,
     #[br(parse_with = binrw::helpers::until_eof)]
     pub padding: Vec<u8>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 /// Event input pin source.
@@ -65,6 +71,11 @@ impl From<u8> for EventPolarity {
 }
 
 impl ExtEvent {
+    /// Check the Rev 1 field against `revision` before encoding.
+    pub(crate) fn check_revision(&self) -> Result<(), EncodeError> {
+        check_optional("pvt_age", self.pvt_age.is_some(), 1, self.revision)
+    }
+
     /// Event input pin.
     pub fn event_source(&self) -> EventSource {
         EventSource::from(self.source)
@@ -9,6 +9,7 @@ use super::pvt_geodetic::{Datum, PvtMode};
 // Same structure as INSNavCart, but at external event time.
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct ExtEventINSNavCart {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -40,7 +41,7 @@ pub struct ExtEventINSNavCart {
     #[br(map = binrw_util::map_datum)]
     #[bw(map = binrw_util::unmap_datum)]
     pub datum: Option<Datum>,
-    _reserved: u8,
+    pub(crate) _reserved: u8,
     pub sb_list: u16,
 
     #[br(if(sb_list & 1 == 1))]
@@ -53,6 +54,10 @@ pub struct ExtEventINSNavCart {
     pub vel: Option<ExtEventINSNavCartVel>,
     #[br(if((sb_list >> 4) & 1 == 1))]
     pub vel_std_dev: Option<ExtEventINSNavCartVelStdDev>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 #[binrw]
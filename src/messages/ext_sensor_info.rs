@@ -5,6 +5,7 @@ use binrw::binrw;
 // ExtSensorInfo Block 4222
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct ExtSensorInfo {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -16,6 +17,10 @@ pub struct ExtSensorInfo {
     pub sensor_model: u8,
     #[br(parse_with = binrw::helpers::until_eof)]
     pub data: Vec<u8>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 impl ExtSensorInfo {
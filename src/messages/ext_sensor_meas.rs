@@ -5,6 +5,7 @@ use binrw::binrw;
 // External Sensor Measurement Block 4050
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct ExtSensorMeas {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -14,9 +15,13 @@ pub struct ExtSensorMeas {
     pub wnc: Option<u16>,
     pub n: u8,
     pub sb_length: u8,
-    #[br(args { count: usize::from(n), inner: (usize::from(sb_length),) }, map = binrw_util::unwrap_subblocks)]
-    #[bw(args_raw = (usize::from(*sb_length),), map = binrw_util::wrap_subblocks)]
+    #[br(parse_with = binrw_util::read_subblocks, args(usize::from(n), usize::from(sb_length)))]
+    #[bw(write_with = binrw_util::write_subblocks, args(usize::from(*sb_length)))]
     pub ext_sensor_meas_set: Vec<ExtSensorMeasSet>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 #[repr(u8)]
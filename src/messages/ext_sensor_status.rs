@@ -120,6 +120,7 @@ impl fmt::Display for ExtSensorModel {
 // ExtSensorStatus Block 4223
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct ExtSensorStatus {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -131,6 +132,10 @@ pub struct ExtSensorStatus {
     pub sensor_model: u8,
     #[br(parse_with = binrw::helpers::until_eof)]
     pub data: Vec<u8>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 impl ExtSensorStatus {
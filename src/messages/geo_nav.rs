@@ -4,6 +4,7 @@ use binrw::binrw;
 // GEONav Block 5896
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct GEONav {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -27,4 +28,8 @@ pub struct GEONav {
     pub zgdd: f64,
     pub a_gf0: f32,
     pub a_gf1: f32,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
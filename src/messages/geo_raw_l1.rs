@@ -4,6 +4,7 @@ use binrw::binrw;
 // GEORawL1 Block 4020
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct GEORawL1 {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -19,6 +20,10 @@ pub struct GEORawL1 {
     pub rx_channel: u8,
     // 250 bits stored in 8 u32s (256 bits total, last 6 bits unused)
     pub nav_bits: [u32; 8],
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 impl GEORawL1 {
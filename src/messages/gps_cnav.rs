@@ -5,6 +5,7 @@ use binrw::binrw;
 // GPSCNav Block 4042
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct GPSCNav {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -61,6 +62,10 @@ pub struct GPSCNav {
     pub isc_l5q5: Option<f32>,
     #[br(parse_with = binrw::helpers::until_eof)]
     pub padding: Vec<u8>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 impl GPSCNav {
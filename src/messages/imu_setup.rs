@@ -4,6 +4,7 @@ use binrw::binrw;
 // IMU Setup Block 4224
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct ImuSetup {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -11,7 +12,7 @@ pub struct ImuSetup {
     #[br(map = binrw_util::map_u2)]
     #[bw(map = binrw_util::unmap_u2)]
     pub wnc: Option<u16>,
-    _reserved: u8,
+    pub(crate) _reserved: u8,
     // TODO: create SerialPort enum for future serial port info
     pub serial_port: u8,
     #[br(map = binrw_util::map_f4)]
@@ -32,4 +33,8 @@ pub struct ImuSetup {
     #[br(map = binrw_util::map_f4)]
     #[bw(map = binrw_util::unmap_f4)]
     pub theta_z_deg: Option<f32>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
@@ -8,6 +8,7 @@ use super::pvt_geodetic::{Datum, PvtMode};
 // INSNavCart Block 4225
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct INSNavCart {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -41,7 +42,7 @@ pub struct INSNavCart {
     #[br(map = binrw_util::map_datum)]
     #[bw(map = binrw_util::unmap_datum)]
     pub datum: Option<Datum>,
-    _reserved: u8,
+    pub(crate) _reserved: u8,
     pub sb_list: u16,
 
     #[br(if(sb_list & 1 == 1))]
@@ -60,6 +61,10 @@ pub struct INSNavCart {
     pub att_cov: Option<INSNavCartAttCov>,
     #[br(if((sb_list >> 7) & 1 == 1))]
     pub vel_cov: Option<INSNavCartVelCov>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 #[binrw]
@@ -129,6 +129,7 @@ impl Default for INSError {
 // INS Nav Geod Block 4226
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct INSNavGeod {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -165,7 +166,7 @@ pub struct INSNavGeod {
     #[br(map = binrw_util::map_datum)]
     #[bw(map = binrw_util::unmap_datum)]
     pub datum: Option<Datum>,
-    _reserved: u8,
+    pub(crate) _reserved: u8,
     // TODO: unpack into an SBList type so we know what INSNav Sub Blocks we can parse
     pub sb_list: u16,
 
@@ -185,6 +186,10 @@ pub struct INSNavGeod {
     pub att_cov: Option<INSNavGeodAttCov>,
     #[br(if((sb_list >> 7) & 1 == 1))]
     pub vel_cov: Option<INSNavGeodVelCov>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 #[binrw]
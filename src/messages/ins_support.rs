@@ -8,6 +8,7 @@ use binrw::binrw;
 // parse these messages. For now, we store the raw bytes.
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct INSSupport {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -20,4 +21,8 @@ pub struct INSSupport {
     // to properly parse. We store the raw bytes for future processing.
     #[br(parse_with = binrw::helpers::until_eof)]
     pub raw_data: Vec<u8>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
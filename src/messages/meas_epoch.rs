@@ -1,11 +1,12 @@
 use crate::binrw_util;
-use crate::{NestedBlock, NestedHeader, SubBlock};
+use crate::{NestedBlock, NestedHeader};
 use alloc::vec::Vec;
 use binrw::binrw;
 
 // MeasEpoch Block 4027
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct MeasEpoch {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -19,18 +20,22 @@ pub struct MeasEpoch {
     pub common_flags: u8,
     pub cum_clk_jumps: u8,
     pub rev1: u8,
-    #[br(args { count: usize::from(n1), inner: (usize::from(sb1_length), usize::from(sb2_length)) },
-         map = |v: Vec<NestedBlock<MeasEpochChannelType1Header, MeasEpochChannelType2>>| v.into_iter().map(MeasEpochChannelType1::from).collect())]
-    #[bw(args_raw = (usize::from(*sb1_length), usize::from(*sb2_length)),
-         map = |v: &Vec<MeasEpochChannelType1>| v.iter().cloned().map(NestedBlock::from).collect::<Vec<NestedBlock<MeasEpochChannelType1Header, MeasEpochChannelType2>>>())]
+    #[br(parse_with = binrw_util::read_nested_blocks,
+         args(usize::from(n1), usize::from(sb1_length), usize::from(sb2_length)))]
+    #[bw(write_with = binrw_util::write_nested_blocks,
+         args(usize::from(*sb1_length), usize::from(*sb2_length)))]
     pub channel_type1: Vec<MeasEpochChannelType1>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 // First-level header of a MeasEpochChannelType1 sub-block. Internal wire type;
-// the flat public MeasEpochChannelType1 is bridged from NestedBlock.
+// the flat public MeasEpochChannelType1 is bridged to it through NestedBlock.
 #[binrw]
 #[derive(Clone, Debug)]
-struct MeasEpochChannelType1Header {
+pub(crate) struct MeasEpochChannelType1Header {
     pub rx_channel: u8,
     pub type_field: u8,
     pub svid: u8,
@@ -74,46 +79,48 @@ pub struct MeasEpochChannelType1 {
     pub channel_type2: Vec<MeasEpochChannelType2>,
 }
 
-impl From<NestedBlock<MeasEpochChannelType1Header, MeasEpochChannelType2>> for MeasEpochChannelType1 {
-    fn from(nb: NestedBlock<MeasEpochChannelType1Header, MeasEpochChannelType2>) -> Self {
+impl NestedBlock for MeasEpochChannelType1 {
+    type Header = MeasEpochChannelType1Header;
+    type Item = MeasEpochChannelType2;
+
+    fn from_parts(header: MeasEpochChannelType1Header, channel_type2: Vec<Self::Item>) -> Self {
         MeasEpochChannelType1 {
-            rx_channel: nb.header.rx_channel,
-            type_field: nb.header.type_field,
-            svid: nb.header.svid,
-            misc: nb.header.misc,
-            code_lsb: nb.header.code_lsb,
-            doppler: nb.header.doppler,
-            carrier_lsb: nb.header.carrier_lsb,
-            carrier_msb: nb.header.carrier_msb,
-            cn0: nb.header.cn0,
-            lock_time: nb.header.lock_time,
-            obs_info: nb.header.obs_info,
-            n2: nb.header.n2,
-            channel_type2: nb.items.into_iter().map(SubBlock::into_inner).collect(),
+            rx_channel: header.rx_channel,
+            type_field: header.type_field,
+            svid: header.svid,
+            misc: header.misc,
+            code_lsb: header.code_lsb,
+            doppler: header.doppler,
+            carrier_lsb: header.carrier_lsb,
+            carrier_msb: header.carrier_msb,
+            cn0: header.cn0,
+            lock_time: header.lock_time,
+            obs_info: header.obs_info,
+            n2: header.n2,
+            channel_type2,
         }
     }
-}
 
-impl From<MeasEpochChannelType1> for NestedBlock<MeasEpochChannelType1Header, MeasEpochChannelType2> {
-    fn from(ct1: MeasEpochChannelType1) -> Self {
-        NestedBlock {
-            header: MeasEpochChannelType1Header {
-                rx_channel: ct1.rx_channel,
-                type_field: ct1.type_field,
-                svid: ct1.svid,
-                misc: ct1.misc,
-                code_lsb: ct1.code_lsb,
-                doppler: ct1.doppler,
-                carrier_lsb: ct1.carrier_lsb,
-                carrier_msb: ct1.carrier_msb,
-                cn0: ct1.cn0,
-                lock_time: ct1.lock_time,
-                obs_info: ct1.obs_info,
-                n2: ct1.n2,
-            },
-            items: ct1.channel_type2.into_iter().map(SubBlock::from).collect(),
+    fn header(&self) -> MeasEpochChannelType1Header {
+        MeasEpochChannelType1Header {
+            rx_channel: self.rx_channel,
+            type_field: self.type_field,
+            svid: self.svid,
+            misc: self.misc,
+            code_lsb: self.code_lsb,
+            doppler: self.doppler,
+            carrier_lsb: self.carrier_lsb,
+            carrier_msb: self.carrier_msb,
+            cn0: self.cn0,
+            lock_time: self.lock_time,
+            obs_info: self.obs_info,
+            n2: self.n2,
         }
     }
+
+    fn items(&self) -> &[Self::Item] {
+        &self.channel_type2
+    }
 }
 
 #[binrw]
@@ -44,6 +44,7 @@ pub mod pos_cov_geodetic;
 pub mod pvt_cartesian;
 pub mod pvt_geodetic;
 pub mod quality_ind;
+pub mod raw_block;
 pub mod receiver_setup;
 pub mod receiver_status;
 pub mod receiver_time;
@@ -119,6 +120,7 @@ pub use pvt_geodetic::{
     Datum, DiffCorrType, PVTGeodetic, PvtError, PvtMode, PvtModeFlags, RaimIntegrity, WACorrFlags,
 };
 pub use quality_ind::{QualityInd, QualityIndicator};
+pub use raw_block::RawBlock;
 pub use receiver_setup::ReceiverSetup;
 pub use receiver_status::{AGCState, ExtError, ReceiverStatus, RxError, RxState};
 pub use receiver_time::ReceiverTime;
@@ -11,6 +11,7 @@ use super::pvt_geodetic::{
 // Combined PVTCartesian + AttEuler + DOP + ReceiverTime fields.
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct NavCart {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -89,7 +90,7 @@ pub struct NavCart {
     #[bw(map = binrw_util::unmap_u2)]
     pub vel_v_acc: Option<u16>,
     pub misc: u8,
-    _reserved: u8,
+    pub(crate) _reserved: u8,
     // Attitude fields (from AttEuler)
     mode_att_raw: u16,
     error_att_raw: u8,
@@ -139,6 +140,10 @@ pub struct NavCart {
     pub utc_day: Option<i8>,
     #[br(parse_with = binrw::helpers::until_eof)]
     pub padding: Vec<u8>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 impl NavCart {
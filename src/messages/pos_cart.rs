@@ -9,6 +9,7 @@ use super::pvt_geodetic::{
 // PosCart Block 4044
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct PosCart {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -66,7 +67,7 @@ pub struct PosCart {
     #[bw(map = binrw_util::unmap_u2_zero)]
     pub vdop: Option<u16>,
     pub misc: u8,
-    _reserved: u8,
+    pub(crate) _reserved: u8,
     alert_flag_raw: u8,
     #[br(map = binrw_util::map_datum)]
     #[bw(map = binrw_util::unmap_datum)]
@@ -84,6 +85,10 @@ pub struct PosCart {
     pub signal_info: u32,
     #[br(parse_with = binrw::helpers::until_eof)]
     pub padding: Vec<u8>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 impl PosCart {
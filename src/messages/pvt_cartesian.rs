@@ -1,4 +1,5 @@
 use crate::binrw_util;
+use crate::encoder::{check_optional, check_required, EncodeError};
 use alloc::vec::Vec;
 use binrw::binrw;
 
@@ -9,6 +10,7 @@ use super::pvt_geodetic::{
 // PVTCartesian Block 4006
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct PVTCartesian {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -68,25 +70,46 @@ pub struct PVTCartesian {
     pub mean_corr_age: Option<u16>,
     pub signal_info: u32,
     alert_flag_raw: u8,
-    // Rev 1 fields
-    pub nr_bases: u8,
-    pub ppp_info: u16,
-    #[br(map = binrw_util::map_u2)]
-    #[bw(map = binrw_util::unmap_u2)]
+    // Rev 1 fields, None in revision 0 blocks
+    #[br(if(revision >= 1))]
+    #[bw(if(*revision >= 1), map = |x: &Option<u8>| x.unwrap_or_default())]
+    pub nr_bases: Option<u8>,
+    #[br(if(revision >= 1))]
+    #[bw(if(*revision >= 1), map = |x: &Option<u16>| x.unwrap_or_default())]
+    pub ppp_info: Option<u16>,
+    // Rev 2 fields, None in revision 0 and 1 blocks
+    #[br(if(revision >= 2, None), map = binrw_util::map_u2)]
+    #[bw(if(*revision >= 2), map = binrw_util::unmap_u2)]
     pub latency: Option<u16>,
-    #[br(map = binrw_util::map_u2)]
-    #[bw(map = binrw_util::unmap_u2)]
+    #[br(if(revision >= 2, None), map = binrw_util::map_u2)]
+    #[bw(if(*revision >= 2), map = binrw_util::unmap_u2)]
     pub h_accuracy: Option<u16>,
-    #[br(map = binrw_util::map_u2)]
-    #[bw(map = binrw_util::unmap_u2)]
+    #[br(if(revision >= 2, None), map = binrw_util::map_u2)]
+    #[bw(if(*revision >= 2), map = binrw_util::unmap_u2)]
     pub v_accuracy: Option<u16>,
-    pub misc: u8,
-    // Rev 2 fields
+    #[br(if(revision >= 2))]
+    #[bw(if(*revision >= 2), map = |x: &Option<u8>| x.unwrap_or_default())]
+    pub misc: Option<u8>,
     #[br(parse_with = binrw::helpers::until_eof)]
     pub padding: Vec<u8>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 impl PVTCartesian {
+    /// Check the Rev 1 and Rev 2 fields against `revision` before encoding.
+    pub(crate) fn check_revision(&self) -> Result<(), EncodeError> {
+        let rev = self.revision;
+        check_required("nr_bases", self.nr_bases.is_some(), 1, rev)?;
+        check_required("ppp_info", self.ppp_info.is_some(), 1, rev)?;
+        check_optional("latency", self.latency.is_some(), 2, rev)?;
+        check_optional("h_accuracy", self.h_accuracy.is_some(), 2, rev)?;
+        check_optional("v_accuracy", self.v_accuracy.is_some(), 2, rev)?;
+        check_required("misc", self.misc.is_some(), 2, rev)
+    }
+
     /// PVT mode (bits 0-3 of mode).
     pub fn pvt_mode(&self) -> PvtMode {
         PvtMode::from(self.mode_raw)
@@ -59,6 +59,7 @@ impl From<QualityIndicator> for u16 {
 // Quality Indicator Block 4082
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct QualityInd {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -66,13 +67,17 @@ pub struct QualityInd {
     #[br(map = binrw_util::map_u2)]
     #[bw(map = binrw_util::unmap_u2)]
     pub wnc: Option<u16>,
-    n: u8,
-    reserved: u8,
+    pub(crate) n: u8,
+    pub(crate) reserved: u8,
     #[br(count = usize::from(n), map = |v: Vec<u16>| v.into_iter().map(QualityIndicator::from).collect())]
     #[bw(map = |v: &Vec<QualityIndicator>| v.iter().map(|q| u16::from(*q)).collect::<Vec<u16>>())]
     pub indicators: Vec<QualityIndicator>,
     #[br(parse_with = binrw::helpers::until_eof)]
-    _padding: Vec<u8>,
+    pub(crate) _padding: Vec<u8>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 impl PartialEq for QualityInd {
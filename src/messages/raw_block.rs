@@ -0,0 +1,27 @@
+use crate::binrw_util;
+use alloc::vec::Vec;
+use binrw::binrw;
+
+/// A CRC-checked block whose body is kept undecoded, such as a block this
+/// crate does not model. Encoding it reproduces the original block.
+#[binrw]
+#[derive(Clone, Debug, PartialEq)]
+#[br(import(revision: u8))]
+pub struct RawBlock {
+    /// Block number, bits 0-12 of the block ID.
+    #[brw(ignore)]
+    pub id: u16,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
+    #[br(map = binrw_util::map_u4)]
+    #[bw(map = binrw_util::unmap_u4)]
+    pub tow: Option<u32>,
+    #[br(map = binrw_util::map_u2)]
+    #[bw(map = binrw_util::unmap_u2)]
+    pub wnc: Option<u16>,
+    /// The rest of the body after the time stamp, including any padding.
+    #[br(parse_with = binrw_util::read_to_end)]
+    pub payload: Vec<u8>,
+}
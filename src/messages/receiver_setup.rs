@@ -1,9 +1,12 @@
 use crate::binrw_util;
+use crate::encoder::{check_optional, check_required, EncodeError};
+use alloc::vec::Vec;
 use binrw::binrw;
 
 // Receiver Setup Block 5902
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct ReceiverSetup {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -30,21 +33,65 @@ pub struct ReceiverSetup {
     #[br(map = binrw_util::map_f4)]
     #[bw(map = binrw_util::unmap_f4)]
     pub delta_n: Option<f32>,
-    pub marker_type: [u8; 20],
-    pub gnss_fw_version: [u8; 40],
-    pub product_name: [u8; 40],
-    #[br(map = binrw_util::map_f8)]
-    #[bw(map = binrw_util::unmap_f8)]
+    // Rev 1, None in revision 0 blocks
+    #[br(if(revision >= 1))]
+    #[bw(if(*revision >= 1), map = |x: &Option<[u8; 20]>| x.unwrap_or([0; 20]))]
+    pub marker_type: Option<[u8; 20]>,
+    // Rev 2, None in revision 0 and 1 blocks
+    #[br(if(revision >= 2))]
+    #[bw(if(*revision >= 2), map = |x: &Option<[u8; 40]>| x.unwrap_or([0; 40]))]
+    pub gnss_fw_version: Option<[u8; 40]>,
+    // Rev 3, None in revision 0 to 2 blocks
+    #[br(if(revision >= 3))]
+    #[bw(if(*revision >= 3), map = |x: &Option<[u8; 40]>| x.unwrap_or([0; 40]))]
+    pub product_name: Option<[u8; 40]>,
+    // Rev 4 fields, None in revision 0 to 3 blocks
+    #[br(if(revision >= 4, None), map = binrw_util::map_f8)]
+    #[bw(if(*revision >= 4), map = binrw_util::unmap_f8)]
     pub latitude: Option<f64>,
-    #[br(map = binrw_util::map_f8)]
-    #[bw(map = binrw_util::unmap_f8)]
+    #[br(if(revision >= 4, None), map = binrw_util::map_f8)]
+    #[bw(if(*revision >= 4), map = binrw_util::unmap_f8)]
     pub longitude: Option<f64>,
-    #[br(map = binrw_util::map_f4)]
-    #[bw(map = binrw_util::unmap_f4)]
+    #[br(if(revision >= 4, None), map = binrw_util::map_f4)]
+    #[bw(if(*revision >= 4), map = binrw_util::unmap_f4)]
     pub height: Option<f32>,
-    pub station_code: [u8; 10],
-    pub monument_idx: u8,
-    pub receiver_idx: u8,
-    pub country_code: [u8; 3],
-    pub reserved1: [u8; 21],
+    #[br(if(revision >= 4))]
+    #[bw(if(*revision >= 4), map = |x: &Option<[u8; 10]>| x.unwrap_or_default())]
+    pub station_code: Option<[u8; 10]>,
+    #[br(if(revision >= 4))]
+    #[bw(if(*revision >= 4), map = |x: &Option<u8>| x.unwrap_or_default())]
+    pub monument_idx: Option<u8>,
+    #[br(if(revision >= 4))]
+    #[bw(if(*revision >= 4), map = |x: &Option<u8>| x.unwrap_or_default())]
+    pub receiver_idx: Option<u8>,
+    #[br(if(revision >= 4))]
+    #[bw(if(*revision >= 4), map = |x: &Option<[u8; 3]>| x.unwrap_or_default())]
+    pub country_code: Option<[u8; 3]>,
+    #[br(if(revision >= 4))]
+    #[bw(if(*revision >= 4), map = |x: &Option<[u8; 21]>| x.unwrap_or_default())]
+    pub reserved1: Option<[u8; 21]>,
+    #[br(parse_with = binrw::helpers::until_eof)]
+    pub padding: Vec<u8>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
+}
+
+impl ReceiverSetup {
+    /// Check the Rev 1 to Rev 4 fields against `revision` before encoding.
+    pub(crate) fn check_revision(&self) -> Result<(), EncodeError> {
+        let rev = self.revision;
+        check_required("marker_type", self.marker_type.is_some(), 1, rev)?;
+        check_required("gnss_fw_version", self.gnss_fw_version.is_some(), 2, rev)?;
+        check_required("product_name", self.product_name.is_some(), 3, rev)?;
+        check_optional("latitude", self.latitude.is_some(), 4, rev)?;
+        check_optional("longitude", self.longitude.is_some(), 4, rev)?;
+        check_optional("height", self.height.is_some(), 4, rev)?;
+        check_required("station_code", self.station_code.is_some(), 4, rev)?;
+        check_required("monument_idx", self.monument_idx.is_some(), 4, rev)?;
+        check_required("receiver_idx", self.receiver_idx.is_some(), 4, rev)?;
+        check_required("country_code", self.country_code.is_some(), 4, rev)?;
+        check_required("reserved1", self.reserved1.is_some(), 4, rev)
+    }
 }
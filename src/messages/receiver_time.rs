@@ -4,6 +4,7 @@ use binrw::binrw;
 // ReceiverTime Block 5914
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct ReceiverTime {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -41,6 +42,10 @@ pub struct ReceiverTime {
     pub delta_ls: Option<i8>,
     /// Clock synchronization bit field.
     pub sync_level: u8,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 impl ReceiverTime {
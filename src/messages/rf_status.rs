@@ -44,6 +44,7 @@ impl RFBand {
 // RFStatus Block 4092
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct RFStatus {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -51,17 +52,21 @@ pub struct RFStatus {
     #[br(map = binrw_util::map_u2)]
     #[bw(map = binrw_util::unmap_u2)]
     pub wnc: Option<u16>,
-    n: u8,
+    pub(crate) n: u8,
     pub sb_length: u8,
     /// Bit 0: GNSS signals may not be authentic (spoofing/simulator).
     /// Bit 1: NMA check failed (e.g. Galileo OSNMA).
     pub flags: u8,
-    _reserved: [u8; 3],
-    #[br(args { count: usize::from(n), inner: (usize::from(sb_length),) }, map = binrw_util::unwrap_subblocks)]
-    #[bw(args_raw = (usize::from(*sb_length),), map = binrw_util::wrap_subblocks)]
+    pub(crate) _reserved: [u8; 3],
+    #[br(parse_with = binrw_util::read_subblocks, args(usize::from(n), usize::from(sb_length)))]
+    #[bw(write_with = binrw_util::write_subblocks, args(usize::from(*sb_length)))]
     pub bands: Vec<RFBand>,
     #[br(parse_with = binrw::helpers::until_eof)]
-    _padding: Vec<u8>,
+    pub(crate) _padding: Vec<u8>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 impl RFStatus {
@@ -5,6 +5,7 @@ use binrw::binrw;
 // RxMessage Block 4103
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct RxMessage {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -25,4 +26,8 @@ pub struct RxMessage {
     pub reserved2: [u8; 2],
     #[br(count = usize::from(string_ln))]
     pub message: Vec<u8>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
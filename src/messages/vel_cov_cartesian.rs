@@ -6,6 +6,7 @@ use super::pvt_geodetic::{PvtError, PvtMode, PvtModeFlags};
 // VelCovCartesian Block 5907
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct VelCovCartesian {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -45,6 +46,10 @@ pub struct VelCovCartesian {
     #[br(map = binrw_util::map_f4)]
     #[bw(map = binrw_util::unmap_f4)]
     pub cov_vzd: Option<f32>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
 
 impl VelCovCartesian {
@@ -5,6 +5,7 @@ use binrw::binrw;
 // VelSensorSetup Block 4244
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct VelSensorSetup {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -25,4 +26,8 @@ pub struct VelSensorSetup {
     pub lever_arm_z: Option<f32>,
     #[br(parse_with = binrw::helpers::until_eof)]
     pub padding: Vec<u8>,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
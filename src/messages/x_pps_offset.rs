@@ -4,6 +4,7 @@ use binrw::binrw;
 // xPPSOffset Block 5911
 #[binrw]
 #[derive(Clone, Debug)]
+#[br(import(revision: u8))]
 pub struct XPPSOffset {
     #[br(map = binrw_util::map_u4)]
     #[bw(map = binrw_util::unmap_u4)]
@@ -17,4 +18,8 @@ pub struct XPPSOffset {
     pub time_scale: u8,
     /// Offset of the xPPS pulse from the reference in nanoseconds. Negative when the pulse is in advance.
     pub offset: f32,
+    /// Block revision number, bits 13-15 of the block ID.
+    #[br(calc = revision)]
+    #[bw(ignore)]
+    pub revision: u8,
 }
@@ -0,0 +1,358 @@
+//! Physical observables decoded from MeasEpoch.
+//!
+//! MeasEpoch packs each satellite's first signal in a Type1 sub-block and
+//! its other signals in Type2 sub-blocks holding offsets from the Type1
+//! values. [`MeasEpoch::observations`] applies the scaling from the SBF
+//! reference guide and yields one [`Observation`] per signal, with None for
+//! the do-not-use encodings.
+//!
+//! ```no_run
+//! # #[cfg(feature = "std")]
+//! # fn main() -> std::io::Result<()> {
+//! use libsbf::{reader::SbfReader, MeasEpoch};
+//!
+//! let file = std::fs::File::open("log.sbf")?;
+//! for meas in SbfReader::new(file).only::<MeasEpoch>() {
+//!     for obs in meas?.observations() {
+//!         if let (Some(sat), Some(range)) = (obs.sat, obs.pseudorange_m) {
+//!             println!("{sat} {} {range:.3} m", obs.signal);
+//!         }
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "std"))]
+//! # fn main() {}
+//! ```
+
+use crate::messages::{MeasEpoch, MeasEpochChannelType1, MeasEpochChannelType2};
+use crate::satellite::SatelliteId;
+use crate::signal::SignalType;
+
+/// Speed of light in m/s, as used by GNSS.
+const SPEED_OF_LIGHT: f64 = 299_792_458.0;
+
+/// One signal's observables at the epoch of its MeasEpoch block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Observation {
+    /// None if the SVID names no satellite.
+    pub sat: Option<SatelliteId>,
+    pub signal: SignalType,
+    pub pseudorange_m: Option<f64>,
+    /// Full carrier phase in cycles. None if the signal's carrier frequency
+    /// is unknown.
+    pub carrier_cycles: Option<f64>,
+    pub doppler_hz: Option<f64>,
+    pub cn0_dbhz: Option<f64>,
+    /// Seconds since the carrier phase was last reset.
+    pub lock_time: Option<u16>,
+    /// The carrier phase has a half-cycle ambiguity.
+    pub half_cycle_ambiguity: bool,
+    /// The pseudorange is smoothed by the carrier phase.
+    pub smoothing: bool,
+}
+
+impl MeasEpoch {
+    /// Every signal's observables, satellite by satellite, each satellite's
+    /// Type1 signal first.
+    pub fn observations(&self) -> impl Iterator<Item = Observation> + '_ {
+        self.channel_type1.iter().flat_map(|ch| {
+            let master = ch.observation();
+            let others = ch
+                .channel_type2
+                .iter()
+                .map(move |sig| sig.observation(ch, &master));
+            core::iter::once(master).chain(others)
+        })
+    }
+}
+
+impl MeasEpochChannelType1 {
+    fn observation(&self) -> Observation {
+        let signal = self.signal_type();
+        let code = u64::from(self.misc & 0x0F) << 32 | u64::from(self.code_lsb);
+        let pseudorange_m = (code != 0).then_some(code as f64 * 0.001);
+        Observation {
+            sat: self.satellite(),
+            signal,
+            pseudorange_m,
+            carrier_cycles: carrier_cycles(
+                pseudorange_m,
+                self.carrier_frequency(),
+                self.carrier_msb,
+                self.carrier_lsb,
+            ),
+            doppler_hz: self.doppler.map(|d| f64::from(d) * 0.0001),
+            cn0_dbhz: cn0_dbhz(self.cn0, signal),
+            lock_time: self.lock_time,
+            half_cycle_ambiguity: self.obs_info & 0x04 != 0,
+            smoothing: self.obs_info & 0x01 != 0,
+        }
+    }
+}
+
+impl MeasEpochChannelType2 {
+    /// Observables relative to the Type1 sub-block `ch`, decoded as
+    /// `master`.
+    fn observation(&self, ch: &MeasEpochChannelType1, master: &Observation) -> Observation {
+        let signal = self.signal_type();
+        let frequency = self.carrier_frequency(ch.glonass_freq_nr());
+
+        // Offsets MSB: code offset in bits 0-2, Doppler offset in bits 3-7,
+        // both signed.
+        let code_msb = ((self.offsets_msb << 5) as i8) >> 5;
+        let doppler_msb = (self.offsets_msb as i8) >> 3;
+
+        let pseudorange_m = match (code_msb, self.code_offset_lsb) {
+            (-4, 0) => None,
+            (msb, lsb) => master
+                .pseudorange_m
+                .map(|pr| pr + f64::from(i32::from(msb) * 65536 + i32::from(lsb)) * 0.001),
+        };
+        let doppler_hz = match (doppler_msb, self.doppler_offset_lsb) {
+            (-16, 0) => None,
+            (msb, lsb) => {
+                let offset = f64::from(i32::from(msb) * 65536 + i32::from(lsb)) * 0.0001;
+                let ratio = frequency
+                    .zip(ch.carrier_frequency())
+                    .map(|(f2, f1)| f2 / f1);
+                master
+                    .doppler_hz
+                    .zip(ratio)
+                    .map(|(d, ratio)| d * ratio + offset)
+            }
+        };
+
+        Observation {
+            sat: master.sat,
+            signal,
+            pseudorange_m,
+            carrier_cycles: carrier_cycles(
+                pseudorange_m,
+                frequency,
+                self.carrier_msb,
+                self.carrier_lsb,
+            ),
+            doppler_hz,
+            cn0_dbhz: cn0_dbhz(self.cn0, signal),
+            lock_time: self.lock_time.map(u16::from),
+            half_cycle_ambiguity: self.obs_info & 0x04 != 0,
+            smoothing: self.obs_info & 0x01 != 0,
+        }
+    }
+}
+
+/// Carrier phase from the pseudorange in wavelengths plus the carrier
+/// offset in millicycles.
+fn carrier_cycles(
+    pseudorange_m: Option<f64>,
+    frequency: Option<f64>,
+    msb: i8,
+    lsb: u16,
+) -> Option<f64> {
+    if (msb, lsb) == (-128, 0) {
+        return None;
+    }
+    let offset = f64::from(i32::from(msb) * 65536 + i32::from(lsb)) * 0.001;
+    Some(pseudorange_m? * frequency? / SPEED_OF_LIGHT + offset)
+}
+
+/// C/N0 in 0.25 dB-Hz steps, offset by 10 dB-Hz except for the GPS P(Y)
+/// signals.
+fn cn0_dbhz(cn0: Option<u8>, signal: SignalType) -> Option<f64> {
+    let offset = match signal {
+        SignalType::GpsL1P | SignalType::GpsL2P => 0.0,
+        _ => 10.0,
+    };
+    cn0.map(|cn0| f64::from(cn0) * 0.25 + offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SPEED_OF_LIGHT;
+    use crate::messages::{MeasEpoch, MeasEpochChannelType1, MeasEpochChannelType2};
+    use crate::parser::parse_datagram;
+    use crate::satellite::{Constellation, SatelliteId};
+    use crate::signal::SignalType;
+    use crate::test_fixtures::meas_epoch_block;
+    use crate::Messages;
+
+    use alloc::vec::Vec;
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.unwrap();
+        assert!((actual - expected).abs() < 1e-6, "{actual} != {expected}");
+    }
+
+    fn signal(type_field: u8) -> MeasEpochChannelType2 {
+        MeasEpochChannelType2 {
+            type_field,
+            lock_time: Some(12),
+            cn0: Some(120),
+            // Code offset -1, Doppler offset +2.
+            offsets_msb: 0x07 | 0x02 << 3,
+            carrier_msb: 1,
+            obs_info: 0x04,
+            code_offset_lsb: 0xFFFF,
+            carrier_lsb: 500,
+            doppler_offset_lsb: 100,
+        }
+    }
+
+    fn epoch() -> MeasEpoch {
+        // GPS PRN 5, L1 C/A on the main antenna, with L2P and L5.
+        let gps = MeasEpochChannelType1 {
+            rx_channel: 1,
+            type_field: 0,
+            svid: 5,
+            misc: 0x01,
+            code_lsb: 0x2345_6789,
+            doppler: Some(-12_345_678),
+            carrier_lsb: 1000,
+            carrier_msb: -2,
+            cn0: Some(160),
+            lock_time: Some(300),
+            obs_info: 0x01,
+            n2: 2,
+            channel_type2: [signal(2), signal(4)].into_iter().collect(),
+        };
+        // GLONASS slot 1 on frequency channel -7, L1 C/A with L2 C/A, and
+        // all do-not-use values.
+        let glonass = MeasEpochChannelType1 {
+            rx_channel: 2,
+            type_field: 8,
+            svid: 38,
+            misc: 0,
+            code_lsb: 0,
+            doppler: None,
+            carrier_lsb: 0,
+            carrier_msb: -128,
+            cn0: None,
+            lock_time: None,
+            obs_info: 1 << 3,
+            n2: 1,
+            channel_type2: [MeasEpochChannelType2 {
+                offsets_msb: 0x04 | 0x10 << 3,
+                code_offset_lsb: 0,
+                doppler_offset_lsb: 0,
+                ..signal(11)
+            }]
+            .into_iter()
+            .collect(),
+        };
+        MeasEpoch {
+            tow: Some(490403000),
+            wnc: Some(2360),
+            n1: 2,
+            sb1_length: 20,
+            sb2_length: 12,
+            common_flags: 0,
+            cum_clk_jumps: 0,
+            rev1: 0,
+            channel_type1: [gps, glonass].into_iter().collect(),
+            revision: 0,
+        }
+    }
+
+    #[test]
+    fn test_observations() {
+        let epoch = epoch();
+        let obs: Vec<_> = epoch.observations().collect();
+        assert_eq!(obs.len(), 5);
+        let gps = SatelliteId::new(Constellation::Gps, 5);
+        assert!(obs[..3].iter().all(|o| o.sat == gps));
+
+        let l1 = &obs[0];
+        let pr1 = 4_886_718.345;
+        assert_eq!(l1.signal, SignalType::GpsL1CA);
+        assert_close(l1.pseudorange_m, pr1);
+        assert_close(
+            l1.carrier_cycles,
+            pr1 * 1575.42e6 / SPEED_OF_LIGHT + (-2.0 * 65536.0 + 1000.0) * 0.001,
+        );
+        assert_close(l1.doppler_hz, -1234.5678);
+        assert_close(l1.cn0_dbhz, 50.0);
+        assert_eq!(l1.lock_time, Some(300));
+        assert!(l1.smoothing && !l1.half_cycle_ambiguity);
+
+        // GPS L2P: no C/N0 offset, Doppler scaled by the carrier ratio.
+        let l2 = &obs[1];
+        assert_eq!(l2.signal, SignalType::GpsL2P);
+        let pr2 = pr1 + (-65536.0 + 65535.0) * 0.001;
+        assert_close(l2.pseudorange_m, pr2);
+        assert_close(
+            l2.carrier_cycles,
+            pr2 * 1227.60e6 / SPEED_OF_LIGHT + (65536.0 + 500.0) * 0.001,
+        );
+        assert_close(
+            l2.doppler_hz,
+            -1234.5678 * 1227.60 / 1575.42 + (2.0 * 65536.0 + 100.0) * 0.0001,
+        );
+        assert_close(l2.cn0_dbhz, 30.0);
+        assert_eq!(l2.lock_time, Some(12));
+        assert!(!l2.smoothing && l2.half_cycle_ambiguity);
+        assert_close(obs[2].cn0_dbhz, 40.0);
+
+        let glo = &obs[3];
+        assert_eq!(glo.sat, SatelliteId::new(Constellation::Glonass, 1));
+        assert_eq!(glo.signal, SignalType::GloL1CA);
+        assert_eq!(glo.pseudorange_m, None);
+        assert_eq!(glo.carrier_cycles, None);
+        assert_eq!(glo.doppler_hz, None);
+        assert_eq!(glo.cn0_dbhz, None);
+        assert_eq!(glo.lock_time, None);
+
+        let glo_l2 = &obs[4];
+        assert_eq!(glo_l2.signal, SignalType::GloL2CA);
+        assert_eq!(glo_l2.pseudorange_m, None);
+        assert_eq!(glo_l2.doppler_hz, None);
+        assert_close(glo_l2.cn0_dbhz, 40.0);
+    }
+
+    #[test]
+    fn test_observations_from_block() {
+        let Ok(Messages::MeasEpoch(epoch)) = parse_datagram(&meas_epoch_block()) else {
+            panic!("expected MeasEpoch");
+        };
+        assert_eq!(epoch.revision, 1);
+        let obs: Vec<_> = epoch.observations().collect();
+        assert_eq!(obs.len(), 4);
+
+        let pr1 = 4_886_718.345;
+        assert_eq!(obs[0].signal, SignalType::GpsL1CA);
+        assert_close(obs[0].pseudorange_m, pr1);
+        assert_close(obs[0].doppler_hz, -1234.5678);
+        assert_close(obs[0].cn0_dbhz, 50.0);
+
+        let l5 = &obs[1];
+        assert_eq!(l5.sat, SatelliteId::new(Constellation::Gps, 5));
+        assert_eq!(l5.signal, SignalType::GpsL5);
+        assert_close(l5.pseudorange_m, pr1 - 0.001);
+        assert_close(
+            l5.carrier_cycles,
+            (pr1 - 0.001) * 1176.45e6 / SPEED_OF_LIGHT + 66.036,
+        );
+        assert_close(l5.doppler_hz, -1234.5678 * 1176.45 / 1575.42 + 13.1172);
+        assert_close(l5.cn0_dbhz, 40.0);
+        assert_eq!(l5.lock_time, Some(12));
+
+        let (glo_l1, glo_l2) = (&obs[2], &obs[3]);
+        assert_eq!(glo_l1.sat, SatelliteId::new(Constellation::Glonass, 1));
+        assert_eq!(glo_l1.signal, SignalType::GloL1CA);
+        assert_close(glo_l1.pseudorange_m, 20_000_000.0);
+        assert_close(
+            glo_l1.carrier_cycles,
+            20_000_000.0 * 1602.5625e6 / SPEED_OF_LIGHT,
+        );
+        assert_close(glo_l1.doppler_hz, 100.0);
+        assert_close(glo_l1.cn0_dbhz, 40.0);
+        assert_eq!(glo_l2.signal, SignalType::GloL2CA);
+        assert_close(glo_l2.pseudorange_m, 20_000_000.0);
+        assert_close(
+            glo_l2.carrier_cycles,
+            20_000_000.0 * 1246.4375e6 / SPEED_OF_LIGHT,
+        );
+        assert_close(glo_l2.doppler_hz, 100.0 * 1246.4375 / 1602.5625);
+        assert_close(glo_l2.cn0_dbhz, 36.0);
+    }
+}
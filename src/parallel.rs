@@ -0,0 +1,234 @@
+//! Decode large SBF log files on every core with [rayon].
+//!
+//! The log is memory-mapped and split into chunks at blocks with a valid
+//! header and CRC. The chunks are decoded in parallel, and the results come
+//! back in file order, as [`crate::reader::SbfReader::framed`] would yield
+//! them.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use libsbf::parallel::ParallelDecoder;
+//! use libsbf::Messages;
+//!
+//! fn main() -> std::io::Result<()> {
+//!     let decoded = ParallelDecoder::new().decode_file("log.sbf")?;
+//!     for framed in &decoded.frames {
+//!         if let Messages::ExtSensorMeas(m) = &framed.msg {
+//!             println!("{}: {:?}", framed.offset, m.tow);
+//!         }
+//!     }
+//!     eprintln!("{:?}", decoded.stats);
+//!     Ok(())
+//! }
+//! ```
+
+use crate::parser::{
+    decode_frames, next_block_start, BlockFilter, Framed, ParseOptions, ParserStats,
+};
+
+use memmap2::Mmap;
+use rayon::prelude::*;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+/// Chunk size [`ParallelDecoder::new`] aims for.
+const DEFAULT_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+/// Decodes SBF data in parallel chunks. See the [module docs](self).
+#[cfg_attr(docsrs, doc(cfg(feature = "parallel")))]
+#[derive(Debug, Clone)]
+pub struct ParallelDecoder {
+    filter: BlockFilter,
+    options: ParseOptions,
+    chunk_size: usize,
+}
+
+/// Everything [`ParallelDecoder`] decoded, in file order.
+#[cfg_attr(docsrs, doc(cfg(feature = "parallel")))]
+#[derive(Debug)]
+pub struct Decoded {
+    pub frames: Vec<Framed>,
+    /// Counters for the whole input, as an [`crate::parser::SbfParser`]
+    /// would keep them, except that a block cut short by the end of the input
+    /// counts as skipped.
+    pub stats: ParserStats,
+}
+
+impl Default for ParallelDecoder {
+    fn default() -> Self {
+        Self {
+            filter: BlockFilter::all(),
+            options: ParseOptions::default(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+}
+
+impl ParallelDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only decode the blocks `filter` selects.
+    pub fn with_filter(mut self, filter: BlockFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Check blocks as `options` says, e.g. strictly.
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Roughly how many bytes each parallel task decodes, 4 MiB by default.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Memory-map the log at `path` and decode it.
+    ///
+    /// The file must not be truncated while it is decoded: like any memory
+    /// map, that makes the process fault.
+    pub fn decode_file(&self, path: impl AsRef<Path>) -> io::Result<Decoded> {
+        let file = File::open(path)?;
+        // SAFETY: the map is read-only and dropped before returning; the
+        // caller is told not to modify the file meanwhile.
+        let map = unsafe { Mmap::map(&file)? };
+        Ok(self.decode(&map))
+    }
+
+    /// Decode every block in `data`, skipping invalid data as
+    /// [`crate::parser::SbfParser`] does.
+    pub fn decode(&self, data: &[u8]) -> Decoded {
+        // Start each chunk after the first at a block that passes its CRC
+        // check, so no chunk starts in the middle of a block.
+        let mut starts: Vec<usize> = (0..data.len().div_ceil(self.chunk_size).max(1))
+            .into_par_iter()
+            .map(|i| match i {
+                0 => 0,
+                i => next_block_start(data, i * self.chunk_size).unwrap_or(data.len()),
+            })
+            .collect();
+        starts.dedup();
+        starts.push(data.len());
+
+        let chunks: Vec<_> = starts
+            .par_windows(2)
+            .map(|w| (w[0], w[1], self.decode_chunk(data, w[0], w[1])))
+            .collect();
+
+        let mut decoded = Decoded {
+            frames: Vec::new(),
+            stats: ParserStats::default(),
+        };
+        // Where the last block decoded ends.
+        let mut end = 0;
+        for (mut start, chunk_end, (mut frames, mut stats)) in chunks {
+            // A chunk only starts inside a block if a CRC matched by chance.
+            // Decode it again from where that block ends, so that nothing in
+            // the overlap is returned or counted twice.
+            if start < end {
+                start = end.min(chunk_end);
+                (frames, stats) = self.decode_chunk(data, start, chunk_end);
+            }
+            for mut framed in frames {
+                framed.offset += start as u64;
+                end = framed.offset as usize + framed.len;
+                decoded.frames.push(framed);
+            }
+            let total = &mut decoded.stats;
+            total.messages += stats.messages;
+            total.blocks_filtered += stats.blocks_filtered;
+            total.bytes_skipped += stats.bytes_skipped;
+            total.header_errors += stats.header_errors;
+            total.crc_errors += stats.crc_errors;
+            total.payload_errors += stats.payload_errors;
+            total.validation_errors += stats.validation_errors;
+        }
+        decoded
+    }
+
+    /// Decode the blocks that start in `data[start..end]`.
+    fn decode_chunk(&self, data: &[u8], start: usize, end: usize) -> (Vec<Framed>, ParserStats) {
+        let mut stats = ParserStats::default();
+        let frames = decode_frames(
+            &data[start..],
+            end - start,
+            &self.filter,
+            &self.options,
+            &mut stats,
+        );
+        (frames, stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ParallelDecoder;
+    use crate::reader::SbfReader;
+    use crate::test_fixtures::VALID_QUALITY_IND;
+    use crate::{Messages, RawBlock};
+
+    #[test]
+    fn test_parallel_matches_reader() {
+        let mut bad_crc = *VALID_QUALITY_IND;
+        bad_crc[2] ^= 0xFF;
+        let mut data = b"noise".to_vec();
+        for i in 0..50 {
+            data.extend_from_slice(VALID_QUALITY_IND);
+            if i % 7 == 0 {
+                data.extend_from_slice(&bad_crc);
+                data.extend_from_slice(b"$@");
+            }
+        }
+        // Cut the last block short.
+        data.extend_from_slice(&VALID_QUALITY_IND[..20]);
+
+        let mut reader = SbfReader::new(&data[..]).framed();
+        let expected: Vec<_> = (&mut reader).map(|f| f.unwrap().offset).collect();
+
+        for chunk_size in [1, 33, 100, data.len()] {
+            let decoded = ParallelDecoder::new()
+                .with_chunk_size(chunk_size)
+                .decode(&data);
+            let offsets: Vec<_> = decoded.frames.iter().map(|f| f.offset).collect();
+            assert_eq!(offsets, expected, "chunk size {chunk_size}");
+            let mut stats = reader.stats();
+            stats.bytes_skipped += 20;
+            assert_eq!(decoded.stats, stats, "chunk size {chunk_size}");
+        }
+    }
+
+    #[test]
+    fn test_parallel_block_inside_block() {
+        // A block carrying a valid QualityInd block in its body, which a
+        // chunk may start at.
+        let outer = Messages::RawBlock(RawBlock {
+            id: 4999,
+            revision: 0,
+            tow: None,
+            wnc: None,
+            payload: VALID_QUALITY_IND.to_vec(),
+        });
+        let mut data = b"noise".to_vec();
+        data.extend_from_slice(&outer.encode().unwrap());
+        data.extend_from_slice(VALID_QUALITY_IND);
+
+        let mut reader = SbfReader::new(&data[..]).framed();
+        let expected: Vec<_> = (&mut reader).map(|f| f.unwrap().offset).collect();
+        assert_eq!(expected, [5, 5 + 48]);
+
+        for chunk_size in 1..=data.len() {
+            let decoded = ParallelDecoder::new()
+                .with_chunk_size(chunk_size)
+                .decode(&data);
+            let offsets: Vec<_> = decoded.frames.iter().map(|f| f.offset).collect();
+            assert_eq!(offsets, expected, "chunk size {chunk_size}");
+            assert_eq!(decoded.stats, reader.stats(), "chunk size {chunk_size}");
+        }
+    }
+}
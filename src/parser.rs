@@ -1,21 +1,19 @@
 extern crate alloc;
+use alloc::format;
+use alloc::string::String;
 use alloc::vec::Vec;
 
 use binrw::io::Cursor;
 use binrw::BinRead;
 
-use crate::{Header, MessageKind, Messages};
+use crate::validate::validate;
+pub use crate::validate::ValidationError;
+use crate::{Header, Id, Messages, SbfBlock};
 
 use crc16::*;
 
 use tracing::debug;
 
-#[derive(Debug)]
-pub enum Error {
-    InvalidHeaderCRC,
-    BinRWError(binrw::Error),
-}
-
 /// Maximum SBF message size that fits in a single UDP datagram.
 /// UDP datagram max = 65535 bytes, minus 8-byte UDP header = 65527 byte payload.
 /// SBF messages (including their 8-byte header) must fit within this.
@@ -36,132 +34,766 @@ pub enum DatagramError {
     InvalidPayload,
     /// Message length exceeds max UDP payload size (65527 bytes).
     ExceedsMaxUdpPayload(u16),
+    /// Strict parsing found the decoded block inconsistent.
+    Validation(ValidationError),
+}
+
+/// How strictly blocks are checked once they pass their CRC check.
+///
+/// By default the decoders are lenient. With `strict` set, a block whose
+/// counts and sub-block lengths disagree with its length, whose reserved
+/// fields are not zero, or whose padding holds more than alignment bytes is
+/// reported as a [`ValidationError`] instead of returned as a message.
+/// Receiver firmware does put values in some fields the reference guide
+/// marks as reserved, so check the `field` of what gets reported.
+///
+/// ```
+/// use libsbf::parser::{ParseOptions, SbfParser};
+///
+/// let parser = SbfParser::new().with_options(ParseOptions { strict: true });
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    pub strict: bool,
 }
 
-enum ParseError {
+pub(crate) enum ParseError {
     IncompleteData,
+    /// The CRC was valid, but the filter rejected the `len` byte block.
+    Filtered {
+        block_id: u16,
+        len: usize,
+    },
     InvalidHeader,
-    InvalidCRC,
-    InvalidPayload,
-    SyncNotFound,
+    InvalidCRC {
+        block_id: u16,
+    },
+    /// The CRC was valid, so the whole `len` byte block can be skipped.
+    InvalidPayload {
+        block_id: u16,
+        len: usize,
+        error: binrw::Error,
+    },
+    /// The block decoded, but failed strict validation.
+    Invalid {
+        len: usize,
+        error: ValidationError,
+    },
 }
 
+/// The parsed value and the size of its frame (sync through padding).
 type Result<T> = core::result::Result<(T, usize), ParseError>;
 
 // Constants for our parser.
 const MIN_MESSAGE_SIZE: usize = 8; // 2 sync bytes + 6 header bytes
 
-fn parse_message(input: &[u8]) -> Result<Messages> {
-    // Make sure the input isn't empty
-    if input.is_empty() {
-        debug!("Incomplete data, don't have enough for sync");
-        return Err(ParseError::IncompleteData);
-    }
-
-    // Find the sync sequence "$@".
-    let sync_index = input
-        .windows(2)
-        .position(|w| w == b"$@")
-        .ok_or(ParseError::SyncNotFound)?;
+/// Position of the first sync sequence "$@" in `input`.
+fn find_sync(input: &[u8]) -> Option<usize> {
+    memchr::memmem::find(input, b"$@")
+}
 
+/// Check the header and CRC of the block whose sync sequence starts `input`,
+/// returning its block ID and complete frame. Blocks longer than `max_len`
+/// are rejected as invalid headers, since they could never be buffered whole.
+pub(crate) fn check_frame(
+    input: &[u8],
+    max_len: usize,
+) -> core::result::Result<(Id, &[u8]), ParseError> {
     // Make sure there's enough data for sync, header, and payload.
-    if input.len() < sync_index + MIN_MESSAGE_SIZE {
+    if input.len() < MIN_MESSAGE_SIZE {
         debug!("Incomplete data, don't have enough for sync and header");
         return Err(ParseError::IncompleteData);
     }
 
     // Extract and validate the header.
-    let header_start = sync_index + 2;
+    let header_start = 2;
     let header_end = header_start + 6;
     let header_slice = &input[header_start..header_end];
     let header: [u8; 6] = header_slice.try_into().unwrap();
 
     let h = Header::read_le(&mut Cursor::new(&header)).map_err(|_| ParseError::InvalidHeader)?;
-    if h.length % 4 != 0 || h.length < 8 {
+    if h.length % 4 != 0 || h.length < 8 || usize::from(h.length) > max_len {
         debug!("Invalid header length: {}", h.length);
         return Err(ParseError::InvalidHeader);
     }
 
     // Ensure we have the complete payload.
     let total_size = 2 + 6 + (h.length as usize) - 8;
-    if input.len() < sync_index + total_size {
+    if input.len() < total_size {
         debug!("Don't have full message.");
         return Err(ParseError::IncompleteData);
     }
 
-    // Build the message.
-    let payload_start = header_end;
-    let payload = input[payload_start..payload_start + (h.length as usize) - 8].to_vec();
-    let mut full_block = Vec::with_capacity(4 + payload.len());
-    full_block.extend_from_slice(&header[2..]);
-    full_block.extend_from_slice(&payload);
-    let crc = State::<XMODEM>::calculate(full_block.as_slice());
-
+    // The CRC covers the block ID, length and payload, which are contiguous
+    // in the input.
+    let crc = State::<XMODEM>::calculate(&input[header_start + 2..total_size]);
     if h.crc != crc {
         debug!("Invalid CRC for {:?}", h.block_id.message_type());
-        return Err(ParseError::InvalidCRC);
+        return Err(ParseError::InvalidCRC {
+            block_id: h.block_id.block_number(),
+        });
     }
 
-    let msg_kind = h.block_id.message_type();
-    if let MessageKind::Unsupported = msg_kind {
-        debug!("Unsupported Block ID: {:?}", h.block_id);
-        return Ok((
-            Messages::Unsupported(h.block_id.block_number()),
-            sync_index + total_size,
-        ));
+    Ok((h.block_id, &input[..total_size]))
+}
+
+/// Parse the block whose sync sequence starts `input`, framed by
+/// [`check_frame`].
+fn parse_message(
+    input: &[u8],
+    max_len: usize,
+    filter: &BlockFilter,
+    options: &ParseOptions,
+) -> Result<Messages> {
+    let (id, frame) = check_frame(input, max_len)?;
+    let total_size = frame.len();
+    let payload = &frame[MIN_MESSAGE_SIZE..];
+    let block_id = id.block_number();
+
+    let res = filter
+        .decode(&id, payload)
+        .ok_or(ParseError::Filtered {
+            block_id,
+            len: total_size,
+        })?
+        .map_err(|error| ParseError::InvalidPayload {
+            block_id,
+            len: total_size,
+            error,
+        })?;
+
+    if options.strict {
+        validate(&res, payload.len()).map_err(|error| ParseError::Invalid {
+            len: total_size,
+            error,
+        })?;
     }
 
-    let res = Messages::parse_body(msg_kind, &payload).map_err(|_| ParseError::InvalidPayload)?;
+    Ok((res, total_size))
+}
 
-    Ok((res, sync_index + total_size))
+/// Decode a CRC-checked block body, keeping unsupported blocks as raw bytes.
+pub(crate) fn decode_body(block_id: &Id, payload: &[u8]) -> binrw::BinResult<Messages> {
+    match block_id.message_type() {
+        Some(kind) => Messages::parse_body(kind, block_id.revision(), payload),
+        None => {
+            debug!("Unsupported Block ID: {:?}", block_id);
+            Messages::parse_raw(block_id, payload)
+        }
+    }
 }
 
-pub struct SbfParser {
-    buf: Vec<u8>,
+/// Which blocks get decoded into messages. Blocks that are not selected are
+/// still CRC-checked, then dropped or, with [`BlockFilter::keep_raw`],
+/// returned undecoded as [`Messages::RawBlock`].
+///
+/// ```
+/// use libsbf::parser::{BlockFilter, SbfParser};
+/// use libsbf::{AttEuler, INSNavGeod};
+///
+/// let filter = BlockFilter::none()
+///     .allow_type::<INSNavGeod>()
+///     .allow_type::<AttEuler>()
+///     .allow(4007); // PVTGeodetic
+/// let parser = SbfParser::new().with_filter(filter);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockFilter {
+    /// One bit per 13-bit block number.
+    blocks: [u64; 128],
+    keep_raw: bool,
 }
 
-impl Default for SbfParser {
+impl Default for BlockFilter {
     fn default() -> Self {
-        Self::new()
+        Self::all()
+    }
+}
+
+impl BlockFilter {
+    /// Decode every block. This is the default.
+    pub fn all() -> Self {
+        Self {
+            blocks: [u64::MAX; 128],
+            keep_raw: false,
+        }
+    }
+
+    /// Decode no blocks, until some are allowed.
+    pub fn none() -> Self {
+        Self {
+            blocks: [0; 128],
+            keep_raw: false,
+        }
+    }
+
+    /// Also decode blocks with `block_number`.
+    pub fn allow(mut self, block_number: u16) -> Self {
+        let n = usize::from(block_number & 0x1FFF);
+        self.blocks[n / 64] |= 1 << (n % 64);
+        self
+    }
+
+    /// Also decode blocks of message type `T`.
+    pub fn allow_type<T: SbfBlock>(self) -> Self {
+        self.allow(T::BLOCK_NUMBER)
+    }
+
+    /// Return blocks that are not selected as [`Messages::RawBlock`] instead
+    /// of dropping them.
+    pub fn keep_raw(mut self, keep_raw: bool) -> Self {
+        self.keep_raw = keep_raw;
+        self
+    }
+
+    /// Whether blocks with `block_number` are decoded.
+    pub fn decodes(&self, block_number: u16) -> bool {
+        let n = usize::from(block_number & 0x1FFF);
+        self.blocks[n / 64] & (1 << (n % 64)) != 0
+    }
+
+    /// Decode a CRC-checked body as the filter says, or None to drop it.
+    fn decode(&self, block_id: &Id, payload: &[u8]) -> Option<binrw::BinResult<Messages>> {
+        if self.decodes(block_id.block_number()) {
+            Some(decode_body(block_id, payload))
+        } else if self.keep_raw {
+            Some(Messages::parse_raw(block_id, payload))
+        } else {
+            None
+        }
+    }
+}
+
+/// Something the streaming parser observed in its input, as returned by
+/// [`SbfParser::consume_event`].
+///
+/// Events come in stream order. A block that fails to parse is reported at
+/// its sync sequence, and the bytes it is skipped by follow as part of the
+/// next `Skipped` event: the whole block once its CRC passed, otherwise its
+/// first byte, so a sync inside it is not missed. That event also counts
+/// any noise up to the next sync, so each skipped byte is reported once.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum SbfEvent {
+    /// A complete block passed its CRC check and was decoded.
+    Message(Messages),
+    /// The block at stream `offset` passed its CRC check, but the
+    /// [`BlockFilter`] dropped it without decoding.
+    Filtered { block_id: u16, offset: u64 },
+    /// Bytes that were not part of any valid block were discarded, including
+    /// those of a block that failed after its error event. Reported before
+    /// the next other event, or once the parser runs out of input.
+    Skipped { bytes: usize },
+    /// A sync sequence at stream `offset` was followed by an invalid length.
+    HeaderError { offset: u64 },
+    /// The block at stream `offset` failed its CRC check.
+    CrcError { block_id: u16, offset: u64 },
+    /// The block at stream `offset` passed its CRC check, but its body failed
+    /// to decode. `binrw_context` is the binrw error message.
+    PayloadError {
+        block_id: u16,
+        offset: u64,
+        binrw_context: String,
+    },
+    /// The block at stream `offset` decoded, but failed strict validation,
+    /// see [`ParseOptions`].
+    ValidationError { offset: u64, error: ValidationError },
+}
+
+/// A decoded message and where it was found in the stream, as returned by
+/// [`SbfParser::consume_framed`] and [`crate::reader::SbfReader::framed`].
+#[derive(Debug)]
+pub struct Framed {
+    /// Stream offset of the block's sync bytes.
+    pub offset: u64,
+    /// Size of the framed block: sync, header, body and padding.
+    pub len: usize,
+    /// Block number, bits 0-12 of the block ID.
+    pub block_id: u16,
+    /// Block revision number, bits 13-15 of the block ID.
+    pub revision: u8,
+    /// When the read that completed the block returned. The parser does no
+    /// I/O so leaves this None; [`crate::reader::SbfReader::framed`] sets it.
+    #[cfg(feature = "std")]
+    pub host_time: Option<std::time::Instant>,
+    pub msg: Messages,
+}
+
+/// Running counters kept by [`SbfParser`] over everything it has consumed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ParserStats {
+    /// Blocks decoded into a message.
+    pub messages: u64,
+    /// CRC-valid blocks dropped by the [`BlockFilter`].
+    pub blocks_filtered: u64,
+    /// Bytes discarded because they were not part of any valid block.
+    pub bytes_skipped: u64,
+    /// Sync sequences followed by an invalid length.
+    pub header_errors: u64,
+    /// Blocks that failed their CRC check.
+    pub crc_errors: u64,
+    /// CRC-valid blocks whose body failed to decode.
+    pub payload_errors: u64,
+    /// Decoded blocks that failed strict validation.
+    pub validation_errors: u64,
+}
+
+/// Returned by [`ParserBuffer::append`] when the input does not fit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferFull;
+
+/// Storage for the bytes an [`SbfParser`] has buffered but not yet parsed.
+///
+/// `Vec<u8>` grows as needed. `heapless::Vec<u8, N>` and [`SliceBuffer`]
+/// hold a fixed number of bytes and never allocate; when input arrives that
+/// does not fit, the parser drops its oldest data and reports it as skipped.
+pub trait ParserBuffer {
+    /// The buffered bytes.
+    fn as_slice(&self) -> &[u8];
+    /// Largest number of bytes the buffer can hold.
+    fn capacity(&self) -> usize;
+    /// Append `input`, or leave the buffer unchanged if it does not fit. The
+    /// parser only appends what fits within the capacity.
+    fn append(&mut self, input: &[u8]) -> core::result::Result<(), BufferFull>;
+    /// Remove the first `n` buffered bytes.
+    fn remove_front(&mut self, n: usize);
+}
+
+impl ParserBuffer for Vec<u8> {
+    fn as_slice(&self) -> &[u8] {
+        self
+    }
+
+    fn capacity(&self) -> usize {
+        usize::MAX
+    }
+
+    fn append(&mut self, input: &[u8]) -> core::result::Result<(), BufferFull> {
+        self.extend_from_slice(input);
+        Ok(())
+    }
+
+    fn remove_front(&mut self, n: usize) {
+        self.drain(0..n);
+    }
+}
+
+impl<const N: usize> ParserBuffer for heapless::Vec<u8, N> {
+    fn as_slice(&self) -> &[u8] {
+        self
+    }
+
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    fn append(&mut self, input: &[u8]) -> core::result::Result<(), BufferFull> {
+        self.extend_from_slice(input).map_err(|_| BufferFull)
+    }
+
+    fn remove_front(&mut self, n: usize) {
+        let len = self.len();
+        self.copy_within(n..len, 0);
+        self.truncate(len - n);
+    }
+}
+
+/// A [`ParserBuffer`] over caller-provided memory, such as a `static` array
+/// on a target without a heap.
+pub struct SliceBuffer<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> SliceBuffer<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+}
+
+impl ParserBuffer for SliceBuffer<'_> {
+    fn as_slice(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    fn append(&mut self, input: &[u8]) -> core::result::Result<(), BufferFull> {
+        let end = self.len + input.len();
+        self.buf
+            .get_mut(self.len..end)
+            .ok_or(BufferFull)?
+            .copy_from_slice(input);
+        self.len = end;
+        Ok(())
+    }
+
+    fn remove_front(&mut self, n: usize) {
+        self.buf.copy_within(n..self.len, 0);
+        self.len -= n;
+    }
+}
+
+/// An [`SbfParser`] that buffers at most `N` bytes inline and never
+/// allocates. `N` should be at least the largest block expected plus the
+/// largest chunk passed in at once; longer blocks are reported as header
+/// errors.
+pub type FixedSbfParser<const N: usize> = SbfParser<heapless::Vec<u8, N>>;
+
+pub struct SbfParser<B = Vec<u8>> {
+    buf: B,
+    /// Read cursor: `buf[..start]` has been consumed and is only reclaimed
+    /// once it outweighs the unread data, so each byte is moved at most a
+    /// constant number of times.
+    start: usize,
+    /// Stream offset of `buf[start]`.
+    offset: u64,
+    /// Size of the frame of the last returned message, still at the front of
+    /// the unread data until the next call.
+    frame_len: usize,
+    /// Skipped bytes not yet reported by an `SbfEvent::Skipped`.
+    skipped: usize,
+    /// Event held back while a `Skipped` event is reported first.
+    pending: Option<SbfEvent>,
+    filter: BlockFilter,
+    options: ParseOptions,
+    stats: ParserStats,
+}
+
+impl<B: ParserBuffer + Default> Default for SbfParser<B> {
+    fn default() -> Self {
+        Self::with_buffer(B::default())
     }
 }
 
 impl SbfParser {
     pub fn new() -> Self {
-        Self { buf: Vec::new() }
+        Self::with_buffer(Vec::new())
+    }
+}
+
+impl<B: ParserBuffer> SbfParser<B> {
+    /// A parser that buffers its input in `buf`, e.g. a [`SliceBuffer`].
+    /// Anything `buf` already holds is parsed first.
+    pub fn with_buffer(buf: B) -> Self {
+        Self {
+            buf,
+            start: 0,
+            offset: 0,
+            frame_len: 0,
+            skipped: 0,
+            pending: None,
+            filter: BlockFilter::all(),
+            options: ParseOptions::default(),
+            stats: ParserStats::default(),
+        }
+    }
+
+    /// Only decode the blocks `filter` selects.
+    pub fn with_filter(mut self, filter: BlockFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Check blocks as `options` says, e.g. strictly.
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Counters for everything consumed so far.
+    pub fn stats(&self) -> ParserStats {
+        self.stats
+    }
+
+    /// How many more bytes the buffer can take before a fixed-capacity
+    /// parser has to drop buffered data.
+    pub fn remaining_capacity(&self) -> usize {
+        self.buf.capacity() - (self.unread().len() - self.frame_len)
     }
 
     /// Consume bytes and attempt to parse the message. If we can't
     /// find a message we return None. If we get a message it doesn't
     /// gurantee the whole buffer internal buffer is drained.
     pub fn consume(&mut self, input: &[u8]) -> Option<Messages> {
-        self.buf.extend(input);
+        self.feed(input);
         loop {
-            debug!("Internal Buffer Size: {}", self.buf.len());
-            match parse_message(&self.buf) {
-                Ok((msg, bytes_consumed)) => {
-                    debug!("draining the buffer");
-                    self.buf.drain(0..bytes_consumed);
-                    return Some(msg);
-                }
-                Err(ParseError::IncompleteData) => {
-                    debug!("Incomplete Data, feed us more!");
-                    return None;
-                }
-                Err(
-                    ParseError::InvalidCRC
-                    | ParseError::InvalidHeader
-                    | ParseError::InvalidPayload
-                    | ParseError::SyncNotFound,
-                ) => {
-                    debug!("Parse error, drain the buffer down");
-                    if !self.buf.is_empty() {
-                        self.buf.drain(0..1);
-                    }
+            if let SbfEvent::Message(msg) = self.next_event()? {
+                return Some(msg);
+            }
+        }
+    }
+
+    /// Like [`SbfParser::consume`], but also returns the complete framed
+    /// block (sync, header, body and padding) the message was decoded from.
+    pub fn consume_with_raw(&mut self, input: &[u8]) -> Option<(Messages, Vec<u8>)> {
+        self.feed(input);
+        loop {
+            if let SbfEvent::Message(msg) = self.next_event()? {
+                return Some((msg, self.frame().to_vec()));
+            }
+        }
+    }
+
+    /// Like [`SbfParser::consume`], but also returns the stream offset, length
+    /// and ID of the block the message was decoded from.
+    pub fn consume_framed(&mut self, input: &[u8]) -> Option<Framed> {
+        let msg = self.consume(input)?;
+        Some(Framed {
+            offset: self.offset,
+            len: self.frame_len,
+            block_id: msg.block_number(),
+            revision: msg.revision(),
+            #[cfg(feature = "std")]
+            host_time: None,
+            msg,
+        })
+    }
+
+    /// Consume bytes and return the next thing the parser observed: a
+    /// message, or a diagnostic about data it had to discard. Returns None
+    /// when it needs more data. Like [`SbfParser::consume`], call again with
+    /// an empty slice to drain events still buffered.
+    pub fn consume_event(&mut self, input: &[u8]) -> Option<SbfEvent> {
+        self.feed(input);
+        self.next_event()
+    }
+
+    /// Append input, first reclaiming consumed space when it is at least as
+    /// large as the unread data or the input would not fit otherwise.
+    fn feed(&mut self, mut input: &[u8]) {
+        let capacity = self.buf.capacity();
+        if self.unread().len() + input.len() > capacity {
+            // Out of room: keep the newest data and drop the oldest as skipped.
+            self.advance();
+            let overflow = (self.unread().len() + input.len()).saturating_sub(capacity);
+            let from_buf = overflow.min(self.unread().len());
+            self.discard(from_buf);
+            self.skip(overflow - from_buf);
+            input = &input[overflow - from_buf..];
+        }
+
+        let len = self.buf.as_slice().len();
+        if self.start > 0 && (self.start >= len - self.start || input.len() > capacity - len) {
+            self.buf.remove_front(self.start);
+            self.start = 0;
+        }
+        if self.buf.append(input).is_err() {
+            debug!("Buffer full, skip {} bytes", input.len());
+            self.skip(input.len());
+        }
+    }
+
+    /// The complete frame of the last returned message or filtered block.
+    pub(crate) fn frame(&self) -> &[u8] {
+        &self.unread()[..self.frame_len]
+    }
+
+    fn unread(&self) -> &[u8] {
+        &self.buf.as_slice()[self.start..]
+    }
+
+    /// Move past the frame of the last returned message.
+    fn advance(&mut self) {
+        self.start += self.frame_len;
+        self.offset += self.frame_len as u64;
+        self.frame_len = 0;
+    }
+
+    fn next_event(&mut self) -> Option<SbfEvent> {
+        if let Some(event) = self.pending.take() {
+            return Some(event);
+        }
+
+        debug!("draining the buffer");
+        self.advance();
+
+        debug!("Internal Buffer Size: {}", self.unread().len());
+        match find_sync(self.unread()) {
+            Some(sync_index) => self.discard(sync_index),
+            None => {
+                // Keep a trailing '$' that may start a sync sequence.
+                let unread = self.unread();
+                let keep = usize::from(unread.last() == Some(&b'$'));
+                self.discard(unread.len() - keep);
+                return self.take_skipped();
+            }
+        }
+
+        let offset = self.offset;
+        let mut skip = 1;
+        let event = match parse_message(
+            self.unread(),
+            self.buf.capacity(),
+            &self.filter,
+            &self.options,
+        ) {
+            Ok((msg, bytes_consumed)) => {
+                self.stats.messages += 1;
+                self.frame_len = bytes_consumed;
+                SbfEvent::Message(msg)
+            }
+            Err(ParseError::IncompleteData) => {
+                debug!("Incomplete Data, feed us more!");
+                return self.take_skipped();
+            }
+            Err(ParseError::Filtered { block_id, len }) => {
+                self.stats.blocks_filtered += 1;
+                self.frame_len = len;
+                SbfEvent::Filtered { block_id, offset }
+            }
+            Err(ParseError::InvalidHeader) => {
+                self.stats.header_errors += 1;
+                SbfEvent::HeaderError { offset }
+            }
+            Err(ParseError::InvalidCRC { block_id }) => {
+                self.stats.crc_errors += 1;
+                SbfEvent::CrcError { block_id, offset }
+            }
+            Err(ParseError::InvalidPayload {
+                block_id,
+                len,
+                error,
+            }) => {
+                // The CRC vouches for the framing, so there's no sync to
+                // find inside this block.
+                skip = len;
+                self.stats.payload_errors += 1;
+                SbfEvent::PayloadError {
+                    block_id,
+                    offset,
+                    binrw_context: format!("{error}"),
                 }
             }
+            Err(ParseError::Invalid { len, error }) => {
+                skip = len;
+                self.stats.validation_errors += 1;
+                SbfEvent::ValidationError { offset, error }
+            }
+        };
+
+        // Report the bytes skipped to reach this sync before the event.
+        let skipped = self.take_skipped();
+        if !matches!(event, SbfEvent::Message(_) | SbfEvent::Filtered { .. }) {
+            debug!("Parse error, skip {skip} bytes");
+            self.discard(skip);
+        }
+
+        match skipped {
+            Some(skipped) => {
+                self.pending = Some(event);
+                Some(skipped)
+            }
+            None => Some(event),
         }
     }
+
+    /// Drop `n` bytes from the front of the unread data as skipped.
+    fn discard(&mut self, n: usize) {
+        self.start += n;
+        self.skip(n);
+    }
+
+    /// Count `n` bytes of the stream as skipped.
+    fn skip(&mut self, n: usize) {
+        self.offset += n as u64;
+        self.skipped += n;
+        self.stats.bytes_skipped += n as u64;
+    }
+
+    fn take_skipped(&mut self) -> Option<SbfEvent> {
+        match core::mem::take(&mut self.skipped) {
+            0 => None,
+            bytes => Some(SbfEvent::Skipped { bytes }),
+        }
+    }
+}
+
+/// Offset of the first block at or after `from` in `data` that has a valid
+/// header and CRC, without decoding it.
+#[cfg(feature = "parallel")]
+pub(crate) fn next_block_start(data: &[u8], mut from: usize) -> Option<usize> {
+    let check = BlockFilter::none();
+    while from < data.len() {
+        let sync = from + find_sync(&data[from..])?;
+        let options = ParseOptions::default();
+        if let Err(ParseError::Filtered { .. }) =
+            parse_message(&data[sync..], usize::MAX, &check, &options)
+        {
+            return Some(sync);
+        }
+        from = sync + 1;
+    }
+    None
+}
+
+/// Decode the blocks of `data` whose sync sequence starts before `end`,
+/// skipping invalid data as [`SbfParser`] does and counting into `stats`.
+/// Frame offsets are relative to `data`.
+#[cfg(feature = "parallel")]
+pub(crate) fn decode_frames(
+    data: &[u8],
+    end: usize,
+    filter: &BlockFilter,
+    options: &ParseOptions,
+    stats: &mut ParserStats,
+) -> Vec<Framed> {
+    let mut frames = Vec::new();
+    let mut pos = 0;
+    while pos < end {
+        let sync = match find_sync(&data[pos..]) {
+            Some(i) if pos + i < end => pos + i,
+            _ => {
+                stats.bytes_skipped += (end - pos) as u64;
+                break;
+            }
+        };
+        stats.bytes_skipped += (sync - pos) as u64;
+
+        let skip = match parse_message(&data[sync..], usize::MAX, filter, options) {
+            Ok((msg, len)) => {
+                stats.messages += 1;
+                frames.push(Framed {
+                    offset: sync as u64,
+                    len,
+                    block_id: msg.block_number(),
+                    revision: msg.revision(),
+                    host_time: None,
+                    msg,
+                });
+                pos = sync + len;
+                continue;
+            }
+            Err(ParseError::Filtered { len, .. }) => {
+                stats.blocks_filtered += 1;
+                pos = sync + len;
+                continue;
+            }
+            // A block cut off by the end of the data.
+            Err(ParseError::IncompleteData) => end - sync,
+            Err(ParseError::InvalidHeader) => {
+                stats.header_errors += 1;
+                1
+            }
+            Err(ParseError::InvalidCRC { .. }) => {
+                stats.crc_errors += 1;
+                1
+            }
+            Err(ParseError::InvalidPayload { len, .. }) => {
+                stats.payload_errors += 1;
+                len
+            }
+            Err(ParseError::Invalid { len, .. }) => {
+                stats.validation_errors += 1;
+                len
+            }
+        };
+        stats.bytes_skipped += skip as u64;
+        pos = sync + skip;
+    }
+    frames
 }
 
 /// Parse a single SBF message from a datagram buffer.
@@ -188,6 +820,125 @@ impl SbfParser {
 /// }
 /// ```
 pub fn parse_datagram(datagram: &[u8]) -> core::result::Result<Messages, DatagramError> {
+    let (block_id, frame) = check_datagram(datagram)?;
+    decode_body(&block_id, &frame[8..]).map_err(|_| DatagramError::InvalidPayload)
+}
+
+/// Like [`parse_datagram`], but checks the block as `options` says.
+pub fn parse_datagram_with(
+    datagram: &[u8],
+    options: &ParseOptions,
+) -> core::result::Result<Messages, DatagramError> {
+    let (block_id, frame) = check_datagram(datagram)?;
+    let msg = decode_body(&block_id, &frame[8..]).map_err(|_| DatagramError::InvalidPayload)?;
+    if options.strict {
+        validate(&msg, frame.len() - 8).map_err(DatagramError::Validation)?;
+    }
+    Ok(msg)
+}
+
+/// Like [`parse_datagram`], but only decodes the blocks `filter` selects.
+/// Returns `Ok(None)` for a valid block the filter drops.
+pub fn parse_datagram_filtered(
+    datagram: &[u8],
+    filter: &BlockFilter,
+) -> core::result::Result<Option<Messages>, DatagramError> {
+    let (block_id, frame) = check_datagram(datagram)?;
+    filter
+        .decode(&block_id, &frame[8..])
+        .transpose()
+        .map_err(|_| DatagramError::InvalidPayload)
+}
+
+/// Parse every SBF block in a datagram. Receivers may pack several blocks
+/// into one UDP packet, where [`parse_datagram`] only returns the first.
+///
+/// Bytes outside any block are skipped. After a CRC or header error the
+/// search for the next block resumes one byte past the failed sync; a block
+/// that passed its CRC but failed to decode is skipped whole. A datagram with
+/// no sync sequence at all yields a single [`DatagramError::NoSync`].
+///
+/// # Example
+///
+/// ```no_run
+/// use libsbf::parser::parse_datagrams;
+/// use std::net::UdpSocket;
+///
+/// let socket = UdpSocket::bind("0.0.0.0:28785").unwrap();
+/// let mut buf = [0u8; 65535];
+///
+/// loop {
+///     let len = socket.recv(&mut buf).unwrap();
+///     let mut blocks = parse_datagrams(&buf[..len]);
+///     while let Some(result) = blocks.next() {
+///         match result {
+///             Ok(msg) => println!("{:?}", msg),
+///             Err(e) => eprintln!("Parse error at byte {}: {:?}", blocks.offset(), e),
+///         }
+///     }
+/// }
+/// ```
+pub fn parse_datagrams(datagram: &[u8]) -> Datagrams<'_> {
+    Datagrams {
+        data: datagram,
+        pos: 0,
+        offset: 0,
+        found_sync: false,
+    }
+}
+
+/// Iterator over the blocks of a datagram, created by [`parse_datagrams`].
+#[derive(Clone, Debug)]
+pub struct Datagrams<'a> {
+    data: &'a [u8],
+    /// Where the search for the next block starts.
+    pos: usize,
+    /// Start of the block last returned.
+    offset: usize,
+    found_sync: bool,
+}
+
+impl Datagrams<'_> {
+    /// Byte offset in the datagram of the block the last item came from.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl Iterator for Datagrams<'_> {
+    type Item = core::result::Result<Messages, DatagramError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &self.data[self.pos..];
+        let Some(sync) = find_sync(rest) else {
+            self.pos = self.data.len();
+            if !self.found_sync && !rest.is_empty() {
+                self.found_sync = true;
+                self.offset = 0;
+                return Some(Err(DatagramError::NoSync));
+            }
+            return None;
+        };
+        self.found_sync = true;
+        self.offset = self.pos + sync;
+
+        let (result, advance) = match check_datagram(&self.data[self.offset..]) {
+            Ok((block_id, frame)) => (
+                decode_body(&block_id, &frame[8..]).map_err(|_| DatagramError::InvalidPayload),
+                frame.len(),
+            ),
+            // Nothing after a truncated block.
+            Err(DatagramError::Incomplete) => (Err(DatagramError::Incomplete), rest.len() - sync),
+            Err(e) => (Err(e), 1),
+        };
+        self.pos = self.offset + advance;
+        Some(result)
+    }
+}
+
+/// Check the framing and CRC of the block at the start of `datagram`,
+/// returning its block ID and complete frame.
+pub(crate) fn check_datagram(datagram: &[u8]) -> core::result::Result<(Id, &[u8]), DatagramError> {
     const MIN_MESSAGE_SIZE: usize = 8; // 2 sync + 2 crc + 2 block_id + 2 length
 
     if datagram.len() < MIN_MESSAGE_SIZE {
@@ -225,23 +976,21 @@ pub fn parse_datagram(datagram: &[u8]) -> core::result::Result<Messages, Datagra
         return Err(DatagramError::InvalidCrc);
     }
 
-    let msg_kind = h.block_id.message_type();
-    if let MessageKind::Unsupported = msg_kind {
-        return Ok(Messages::Unsupported(h.block_id.block_number()));
-    }
-
-    // Parse payload
-    let payload = &datagram[8..total_len];
-    let msg = Messages::parse_body(msg_kind, payload).map_err(|_| DatagramError::InvalidPayload)?;
-
-    Ok(msg)
+    Ok((h.block_id, &datagram[..total_len]))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{parse_datagram, DatagramError, SbfParser};
-    use crate::{DOP, Messages, QualityInd, QualityIndicator};
-    use alloc::vec::Vec;
+    use super::{
+        parse_datagram, parse_datagram_filtered, parse_datagram_with, parse_datagrams, BlockFilter,
+        BufferFull, DatagramError, FixedSbfParser, ParseOptions, ParserBuffer, ParserStats,
+        SbfEvent, SbfParser, SliceBuffer, ValidationError,
+    };
+    use crate::test_fixtures::meas_epoch_block;
+    use crate::{
+        BlockId, EncodeError, Messages, QualityInd, QualityIndicator, ReceiverSetup, SbfBlock, DOP,
+    };
+    use alloc::{vec, vec::Vec};
     use crc16::{State, XMODEM};
     use proptest::prelude::*;
 
@@ -275,12 +1024,15 @@ mod tests {
     fn assert_valid_quality_ind(qi: &QualityInd) {
         assert_eq!(qi.tow, Some(490403000));
         assert_eq!(qi.wnc, Some(2360));
-        let expected: Vec<QualityIndicator> = [2571u16, 2572, 1, 2, 2581, 31, 0].map(QualityIndicator::from).to_vec();
+        let expected: Vec<QualityIndicator> = [2571u16, 2572, 1, 2, 2581, 31, 0]
+            .map(QualityIndicator::from)
+            .to_vec();
         assert_eq!(qi.indicators, expected);
     }
 
-    // Helper function to create ReceiverSetup test payload
-    fn create_receiver_setup_payload() -> Vec<u8> {
+    // Helper function to create a ReceiverSetup test payload with the fields
+    // of `revision` and earlier.
+    fn create_receiver_setup_payload(revision: u8) -> Vec<u8> {
         let mut payload = Vec::new();
 
         // TOW and WNc
@@ -329,18 +1081,30 @@ mod tests {
         payload.extend_from_slice(&0.0f32.to_le_bytes());
         payload.extend_from_slice(&0.0f32.to_le_bytes());
         payload.extend_from_slice(&0.0f32.to_le_bytes());
+        if revision < 1 {
+            return payload;
+        }
 
         let mut marker_type = [0u8; 20];
         marker_type[..8].copy_from_slice(b"GEODETIC");
         payload.extend_from_slice(&marker_type);
+        if revision < 2 {
+            return payload;
+        }
 
         let mut fw_version = [0u8; 40];
         fw_version[..7].copy_from_slice(b"FW_V1.0");
         payload.extend_from_slice(&fw_version);
+        if revision < 3 {
+            return payload;
+        }
 
         let mut product_name = [0u8; 40];
         product_name[..9].copy_from_slice(b"MOSAIC-X5");
         payload.extend_from_slice(&product_name);
+        if revision < 4 {
+            return payload;
+        }
 
         // Position
         payload.extend_from_slice(&0.8997f64.to_le_bytes());
@@ -359,8 +1123,31 @@ mod tests {
         payload
     }
 
+    fn create_receiver_setup_message(revision: u8) -> Vec<u8> {
+        let block_id = 5902 | u16::from(revision) << 13;
+        build_sbf_message(block_id, &create_receiver_setup_payload(revision))
+    }
+
     fn create_valid_receiver_setup_message() -> Vec<u8> {
-        build_sbf_message(5902, &create_receiver_setup_payload())
+        create_receiver_setup_message(4)
+    }
+
+    /// Parse the ReceiverSetup fixture of `revision`, checking that it encodes
+    /// back to the same bytes.
+    fn parse_receiver_setup(revision: u8) -> ReceiverSetup {
+        let message = create_receiver_setup_message(revision);
+        let msg = parse_datagram(&message).expect("ReceiverSetup");
+        assert_eq!(msg.revision(), revision);
+        assert_eq!(msg.encode().unwrap(), message);
+        match msg {
+            Messages::ReceiverSetup(setup) => {
+                assert_eq!(&setup.ant_type[..10], b"CHOKE_RING");
+                assert_eq!(setup.delta_n, Some(0.0));
+                assert!(setup.padding.is_empty());
+                setup
+            }
+            other => panic!("expected ReceiverSetup, got {other:?}"),
+        }
     }
 
     // Helper function to create a DOP (block 4001) test payload (24 bytes).
@@ -456,22 +1243,64 @@ mod tests {
                 assert_eq!(setup.delta_h, Some(0.0));
                 assert_eq!(setup.delta_e, Some(0.0));
                 assert_eq!(setup.delta_n, Some(0.0));
-                assert_eq!(&setup.marker_type[..8], b"GEODETIC");
-                assert_eq!(&setup.gnss_fw_version[..7], b"FW_V1.0");
-                assert_eq!(&setup.product_name[..9], b"MOSAIC-X5");
+                assert_eq!(&setup.marker_type.unwrap()[..8], b"GEODETIC");
+                assert_eq!(&setup.gnss_fw_version.unwrap()[..7], b"FW_V1.0");
+                assert_eq!(&setup.product_name.unwrap()[..9], b"MOSAIC-X5");
                 assert!(setup.latitude.is_some());
                 assert!(setup.longitude.is_some());
                 assert_eq!(setup.height, Some(45.0));
-                assert_eq!(&setup.station_code[..5], b"STAT1");
-                assert_eq!(setup.monument_idx, 1);
-                assert_eq!(setup.receiver_idx, 1);
-                assert_eq!(&setup.country_code, b"GBR");
+                assert_eq!(&setup.station_code.unwrap()[..5], b"STAT1");
+                assert_eq!(setup.monument_idx, Some(1));
+                assert_eq!(setup.receiver_idx, Some(1));
+                assert_eq!(setup.country_code, Some(*b"GBR"));
             }
             Some(other) => panic!("Expected ReceiverSetup, got {:?}", other),
             None => panic!("Failed to parse ReceiverSetup message"),
         }
     }
 
+    #[test]
+    fn test_receiver_setup_rev0_has_no_rev1_fields() {
+        let setup = parse_receiver_setup(0);
+        assert_eq!(setup.marker_type, None);
+        assert_eq!(setup.gnss_fw_version, None);
+    }
+
+    #[test]
+    fn test_receiver_setup_rev1_has_no_rev2_fields() {
+        let setup = parse_receiver_setup(1);
+        assert_eq!(&setup.marker_type.unwrap()[..8], b"GEODETIC");
+        assert_eq!(setup.gnss_fw_version, None);
+        assert_eq!(setup.product_name, None);
+    }
+
+    #[test]
+    fn test_receiver_setup_rev2_has_no_rev3_fields() {
+        let setup = parse_receiver_setup(2);
+        assert_eq!(&setup.gnss_fw_version.unwrap()[..7], b"FW_V1.0");
+        assert_eq!(setup.product_name, None);
+        assert_eq!(setup.latitude, None);
+    }
+
+    #[test]
+    fn test_receiver_setup_rev3_has_no_rev4_fields() {
+        let setup = parse_receiver_setup(3);
+        assert_eq!(&setup.product_name.unwrap()[..9], b"MOSAIC-X5");
+        assert_eq!(setup.latitude, None);
+        assert_eq!(setup.height, None);
+        assert_eq!(setup.station_code, None);
+        assert_eq!(setup.monument_idx, None);
+        assert_eq!(setup.country_code, None);
+    }
+
+    #[test]
+    fn test_receiver_setup_rev4_has_all_fields() {
+        let setup = parse_receiver_setup(4);
+        assert_eq!(setup.height, Some(45.0));
+        assert_eq!(setup.receiver_idx, Some(1));
+        assert_eq!(setup.reserved1, Some([0; 21]));
+    }
+
     /// Sanitize noise by making any $@ sequences have invalid headers.
     /// This ensures the parser rejects fake syncs immediately rather than
     /// waiting for more data due to a large claimed message length.
@@ -546,12 +1375,15 @@ mod tests {
         datagram.extend_from_slice(&length.to_le_bytes());
 
         let result = parse_datagram(&datagram);
-        assert!(matches!(result, Err(DatagramError::ExceedsMaxUdpPayload(65528))));
+        assert!(matches!(
+            result,
+            Err(DatagramError::ExceedsMaxUdpPayload(65528))
+        ));
     }
 
     #[test]
     fn test_parse_datagram_unsupported_block_returns_ok() {
-        // Block 1000 is in the SBF range but not in MessageKind.
+        // Block 1000 is in the SBF range but not in BlockId.
         let block_id: u16 = 1000;
         let payload = [0u8; 8]; // arbitrary, length must be multiple of 4
         let length: u16 = (payload.len() + 8) as u16;
@@ -570,11 +1402,43 @@ mod tests {
         datagram.extend_from_slice(&payload);
 
         match parse_datagram(&datagram) {
-            Ok(Messages::Unsupported(id)) => assert_eq!(id, block_id),
-            other => panic!("expected Ok(Unsupported({block_id})), got {other:?}"),
+            Ok(Messages::RawBlock(raw)) => {
+                assert_eq!(raw.id, block_id);
+                assert_eq!(raw.revision, 0);
+                assert_eq!(raw.tow, Some(0));
+                assert_eq!(raw.wnc, Some(0));
+                assert_eq!(raw.payload, [0u8; 2]);
+            }
+            other => panic!("expected Ok(RawBlock({block_id})), got {other:?}"),
         }
     }
 
+    #[test]
+    fn test_raw_block_round_trips() {
+        // Block 1000 revision 2, unknown to this crate.
+        let block_id: u16 = 1000 | (2 << 13);
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&490403000u32.to_le_bytes());
+        payload.extend_from_slice(&2360u16.to_le_bytes());
+        payload.extend_from_slice(&[1, 2, 3, 4, 5, 6]);
+        let message = build_sbf_message(block_id, &payload);
+
+        let mut parser = SbfParser::new();
+        let (msg, raw) = parser.consume_with_raw(&message).expect("raw block");
+        assert_eq!(raw, message);
+        match &msg {
+            Messages::RawBlock(rb) => {
+                assert_eq!(rb.id, 1000);
+                assert_eq!(rb.revision, 2);
+                assert_eq!(rb.tow, Some(490403000));
+                assert_eq!(rb.wnc, Some(2360));
+                assert_eq!(rb.payload, [1, 2, 3, 4, 5, 6]);
+            }
+            other => panic!("expected RawBlock, got {other:?}"),
+        }
+        assert_eq!(msg.encode().unwrap(), message);
+    }
+
     #[test]
     fn test_parse_datagram_unsupported_block_bad_crc_errors() {
         let block_id: u16 = 1000;
@@ -592,6 +1456,562 @@ mod tests {
         assert!(matches!(result, Err(DatagramError::InvalidCrc)));
     }
 
+    #[test]
+    fn test_block_registry() {
+        let blocks: Vec<_> = crate::supported_blocks().collect();
+        assert!(blocks.windows(2).all(|w| w[0].number() < w[1].number()));
+        for block in blocks {
+            assert_eq!(BlockId::from_number(block.number()), Some(block));
+            assert_eq!(BlockId::from_name(block.name()), Some(block));
+        }
+        assert_eq!(
+            BlockId::from_name("QualityInd").map(BlockId::number),
+            Some(4082)
+        );
+        assert_eq!(QualityInd::NAME, "QualityInd");
+        assert_eq!(BlockId::from_number(1000), None);
+        assert_eq!(BlockId::from_name("qualityind"), None);
+
+        let mut datagram = Vec::new();
+        datagram.extend_from_slice(VALID_SYNC);
+        datagram.extend_from_slice(VALID_QUALITY_IND_HEADER);
+        datagram.extend_from_slice(VALID_QUALITY_IND_PAYLOAD);
+        let msg = parse_datagram(&datagram).unwrap();
+        assert_eq!(msg.block_number(), QualityInd::BLOCK_NUMBER);
+        assert_eq!(msg.block(), Some(BlockId::QualityInd));
+
+        let raw = parse_datagram(&build_sbf_message(1000, &[0; 8])).unwrap();
+        assert_eq!((raw.block_number(), raw.block()), (1000, None));
+    }
+
+    // PVTGeodetic (block 4007) body up to AlertFlag, the revision 0 layout.
+    fn create_pvt_geodetic_rev0_payload() -> Vec<u8> {
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&490403000u32.to_le_bytes()); // tow
+        payload.extend_from_slice(&2360u16.to_le_bytes()); // wnc
+        payload.push(1); // mode
+        payload.push(0); // error
+        payload.extend_from_slice(&0.8997f64.to_le_bytes()); // latitude
+        payload.extend_from_slice(&(-0.00223f64).to_le_bytes()); // longitude
+        payload.extend_from_slice(&45.0f64.to_le_bytes()); // height
+        for _ in 0..5 {
+            payload.extend_from_slice(&0.0f32.to_le_bytes()); // undulation, vn, ve, vu, cog
+        }
+        payload.extend_from_slice(&0.0f64.to_le_bytes()); // rx_clk_bias
+        payload.extend_from_slice(&0.0f32.to_le_bytes()); // rx_clk_drift
+        payload.extend_from_slice(&[0, 0, 9, 0]); // time_system, datum, nr_sv, wa_corr_info
+        payload.extend_from_slice(&[0xFF; 4]); // reference_id, mean_corr_age
+        payload.extend_from_slice(&0u32.to_le_bytes()); // signal_info
+        payload.push(0); // alert_flag
+                         // Padding to a multiple of 4 that a revision-blind reader would take
+                         // for NrBases and PPPInfo.
+        payload.extend_from_slice(&[0xAA; 3]);
+        payload
+    }
+
+    #[test]
+    fn test_pvt_geodetic_rev0_has_no_rev1_fields() {
+        let message = build_sbf_message(4007, &create_pvt_geodetic_rev0_payload());
+        let msg = parse_datagram(&message).expect("rev 0 PVTGeodetic");
+        assert_eq!(msg.revision(), 0);
+        match &msg {
+            Messages::PVTGeodetic(pvt) => {
+                assert_eq!(pvt.nr_sv, Some(9));
+                assert_eq!(pvt.nr_bases, None);
+                assert_eq!(pvt.ppp_info, None);
+                assert_eq!(pvt.latency, None);
+                assert_eq!(pvt.misc, None);
+                assert_eq!(pvt.padding, [0xAA; 3]);
+            }
+            other => panic!("expected PVTGeodetic, got {other:?}"),
+        }
+        assert_eq!(msg.encode().unwrap(), message);
+    }
+
+    #[test]
+    fn test_pvt_geodetic_rev2_has_all_fields() {
+        let mut payload = create_pvt_geodetic_rev0_payload();
+        payload.truncate(payload.len() - 3);
+        payload.push(2); // nr_bases
+        payload.extend_from_slice(&0x1234u16.to_le_bytes()); // ppp_info
+        payload.extend_from_slice(&15u16.to_le_bytes()); // latency
+        payload.extend_from_slice(&120u16.to_le_bytes()); // h_accuracy
+        payload.extend_from_slice(&250u16.to_le_bytes()); // v_accuracy
+        payload.push(0); // misc
+        payload.push(0); // padding
+        let message = build_sbf_message(4007 | (2 << 13), &payload);
+
+        let msg = parse_datagram(&message).expect("rev 2 PVTGeodetic");
+        assert_eq!(msg.revision(), 2);
+        match &msg {
+            Messages::PVTGeodetic(pvt) => {
+                assert_eq!(pvt.nr_bases, Some(2));
+                assert_eq!(pvt.ppp_info, Some(0x1234));
+                assert_eq!(pvt.latency, Some(15));
+                assert_eq!(pvt.h_accuracy, Some(120));
+                assert_eq!(pvt.v_accuracy, Some(250));
+                assert_eq!(pvt.misc, Some(0));
+                assert_eq!(pvt.padding, [0]);
+            }
+            other => panic!("expected PVTGeodetic, got {other:?}"),
+        }
+        assert_eq!(msg.encode().unwrap(), message);
+    }
+
+    #[test]
+    fn test_encode_rejects_revision_mismatch() {
+        let rev0 = build_sbf_message(4007, &create_pvt_geodetic_rev0_payload());
+        let Ok(Messages::PVTGeodetic(mut pvt)) = parse_datagram(&rev0) else {
+            panic!("expected PVTGeodetic");
+        };
+
+        // A rev 2 block would need NrBases, PPPInfo and Misc.
+        pvt.revision = 2;
+        assert!(matches!(
+            Messages::PVTGeodetic(pvt.clone()).encode(),
+            Err(EncodeError::RevisionMismatch {
+                field: "nr_bases",
+                revision: 2
+            })
+        ));
+
+        // Rev 2 fields would be dropped from a rev 1 block.
+        pvt.revision = 1;
+        pvt.nr_bases = Some(1);
+        pvt.ppp_info = Some(0);
+        pvt.latency = Some(15);
+        assert!(matches!(
+            Messages::PVTGeodetic(pvt.clone()).encode(),
+            Err(EncodeError::RevisionMismatch {
+                field: "latency",
+                revision: 1
+            })
+        ));
+
+        pvt.latency = None;
+        let mut buf = [0u8; 128];
+        let rev1 = Messages::PVTGeodetic(pvt.clone());
+        assert!(rev1.encode_into(&mut buf).is_ok());
+
+        pvt.revision = 8;
+        assert!(matches!(
+            Messages::PVTGeodetic(pvt).encode_into(&mut buf),
+            Err(EncodeError::RevisionMismatch {
+                field: "revision",
+                revision: 8
+            })
+        ));
+    }
+
+    #[test]
+    fn test_events_report_discarded_data() {
+        let valid = build_sbf_message(4082, VALID_QUALITY_IND_PAYLOAD);
+        let mut bad_crc = valid.clone();
+        bad_crc[2] ^= 0xFF;
+        let mut bad_payload = build_sbf_message(4082, &[0u8; 8]);
+        // A QualityInd claiming more indicators than its body holds.
+        bad_payload[14] = 20;
+        let crc = State::<XMODEM>::calculate(&bad_payload[4..]);
+        bad_payload[2..4].copy_from_slice(&crc.to_le_bytes());
+
+        let mut stream = Vec::new();
+        stream.extend_from_slice(b"noise");
+        stream.extend_from_slice(&bad_crc);
+        stream.extend_from_slice(&bad_payload);
+        stream.extend_from_slice(&valid);
+
+        let mut parser = SbfParser::new();
+        let mut events = Vec::new();
+        let mut input: &[u8] = &stream;
+        while let Some(event) = parser.consume_event(input) {
+            events.push(event);
+            input = &[];
+        }
+
+        assert!(matches!(events[0], SbfEvent::Skipped { bytes: 5 }));
+        assert!(matches!(
+            events[1],
+            SbfEvent::CrcError {
+                block_id: 4082,
+                offset: 5
+            }
+        ));
+        let payload_offset = (5 + bad_crc.len()) as u64;
+        let payload_error = events
+            .iter()
+            .position(|e| matches!(e, SbfEvent::PayloadError { block_id: 4082, offset, .. } if *offset == payload_offset))
+            .expect("payload error event");
+        assert!(payload_error > 1);
+        // The failed block's bytes follow its error event.
+        assert!(matches!(
+            events[payload_error + 1],
+            SbfEvent::Skipped { bytes } if bytes == bad_payload.len()
+        ));
+        match events.last() {
+            Some(SbfEvent::Message(Messages::QualityInd(qi))) => assert_valid_quality_ind(qi),
+            other => panic!("expected the valid QualityInd last, got {other:?}"),
+        }
+
+        let skipped: usize = events
+            .iter()
+            .map(|e| match e {
+                SbfEvent::Skipped { bytes } => *bytes,
+                _ => 0,
+            })
+            .sum();
+        assert_eq!(skipped, 5 + bad_crc.len() + bad_payload.len());
+        assert_eq!(
+            parser.stats(),
+            ParserStats {
+                messages: 1,
+                blocks_filtered: 0,
+                bytes_skipped: skipped as u64,
+                header_errors: 0,
+                crc_errors: 1,
+                payload_errors: 1,
+                validation_errors: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_consume_counts_errors_in_stats() {
+        let valid = build_sbf_message(4082, VALID_QUALITY_IND_PAYLOAD);
+        let mut bad_crc = valid.clone();
+        bad_crc[2] ^= 0xFF;
+
+        let mut stream = bad_crc.clone();
+        stream.extend_from_slice(&valid);
+
+        let mut parser = SbfParser::new();
+        assert!(matches!(
+            parser.consume(&stream),
+            Some(Messages::QualityInd(_))
+        ));
+        assert!(parser.consume(&[]).is_none());
+
+        let stats = parser.stats();
+        assert_eq!(stats.messages, 1);
+        assert_eq!(stats.crc_errors, 1);
+        assert_eq!(stats.bytes_skipped, bad_crc.len() as u64);
+    }
+
+    #[test]
+    fn test_consume_framed_reports_offsets() {
+        let valid = build_sbf_message(4082, VALID_QUALITY_IND_PAYLOAD);
+        let mut stream = b"noise".to_vec();
+        stream.extend_from_slice(&valid);
+        stream.extend_from_slice(&valid);
+
+        let mut parser = SbfParser::new();
+        let first = parser.consume_framed(&stream).unwrap();
+        assert_eq!(first.offset, 5);
+        assert_eq!(first.len, valid.len());
+        assert_eq!((first.block_id, first.revision), (4082, 0));
+        #[cfg(feature = "std")]
+        assert_eq!(first.host_time, None);
+        assert!(matches!(first.msg, Messages::QualityInd(_)));
+
+        let second = parser.consume_framed(&[]).unwrap();
+        assert_eq!(second.offset, (5 + valid.len()) as u64);
+        assert!(parser.consume_framed(&[]).is_none());
+    }
+
+    #[test]
+    fn test_strict_mode_reports_validation_errors() {
+        let strict = ParseOptions { strict: true };
+        let valid = build_sbf_message(4082, VALID_QUALITY_IND_PAYLOAD);
+        assert!(parse_datagram_with(&valid, &strict).is_ok());
+
+        // Non-zero reserved byte.
+        let mut payload = *VALID_QUALITY_IND_PAYLOAD;
+        payload[7] = 1;
+        let reserved = build_sbf_message(4082, &payload);
+        assert!(parse_datagram(&reserved).is_ok());
+        assert_eq!(
+            parse_datagram_with(&reserved, &strict).unwrap_err(),
+            DatagramError::Validation(ValidationError {
+                block_id: 4082,
+                field: "reserved",
+                expected: 0,
+                actual: 1,
+            })
+        );
+
+        // Five indicators, so the last two and the padding are garbage.
+        let mut payload = *VALID_QUALITY_IND_PAYLOAD;
+        payload[6] = 5;
+        let short = build_sbf_message(4082, &payload);
+        assert!(parse_datagram(&short).is_ok());
+        assert_eq!(
+            parse_datagram_with(&short, &strict).unwrap_err(),
+            DatagramError::Validation(ValidationError {
+                block_id: 4082,
+                field: "length",
+                expected: 28,
+                actual: 32,
+            })
+        );
+
+        let mut stream = short.clone();
+        stream.extend_from_slice(&valid);
+        let mut parser = SbfParser::new().with_options(strict);
+        assert!(matches!(
+            parser.consume_event(&stream),
+            Some(SbfEvent::ValidationError {
+                offset: 0,
+                error: ValidationError {
+                    field: "length",
+                    ..
+                }
+            })
+        ));
+        // The invalid block is skipped whole.
+        assert!(matches!(
+            parser.consume_event(&[]),
+            Some(SbfEvent::Skipped { bytes: 32 })
+        ));
+        assert!(matches!(
+            parser.consume_event(&[]),
+            Some(SbfEvent::Message(Messages::QualityInd(_)))
+        ));
+        assert_eq!(parser.stats().validation_errors, 1);
+        assert_eq!(parser.stats().bytes_skipped, short.len() as u64);
+    }
+
+    #[test]
+    fn test_strict_mode_checks_sub_blocks_and_padding() {
+        let strict = ParseOptions { strict: true };
+        let meas_epoch = meas_epoch_block();
+        assert!(parse_datagram_with(&meas_epoch, &strict).is_ok());
+
+        // Non-zero reserved byte after CumClkJumps.
+        let mut body = meas_epoch[8..].to_vec();
+        body[11] = 1;
+        let reserved = build_sbf_message(4027 | 1 << 13, &body);
+        assert_eq!(
+            parse_datagram_with(&reserved, &strict).unwrap_err(),
+            DatagramError::Validation(ValidationError {
+                block_id: 4027,
+                field: "reserved",
+                expected: 0,
+                actual: 1,
+            })
+        );
+
+        // No channels, but a Type1 length shorter than its fields.
+        let mut body = meas_epoch[8..20].to_vec();
+        body[6] = 0;
+        body[7] = 16;
+        let short_sb = build_sbf_message(4027 | 1 << 13, &body);
+        assert!(parse_datagram(&short_sb).is_ok());
+        assert_eq!(
+            parse_datagram_with(&short_sb, &strict).unwrap_err(),
+            DatagramError::Validation(ValidationError {
+                block_id: 4027,
+                field: "sb1_length",
+                expected: 20,
+                actual: 16,
+            })
+        );
+
+        // Seven padding bytes where three align the block.
+        let mut payload = create_pvt_geodetic_rev0_payload();
+        payload.extend_from_slice(&[0; 4]);
+        let padded = build_sbf_message(4007, &payload);
+        assert!(parse_datagram(&padded).is_ok());
+        assert_eq!(
+            parse_datagram_with(&padded, &strict).unwrap_err(),
+            DatagramError::Validation(ValidationError {
+                block_id: 4007,
+                field: "length",
+                expected: 88,
+                actual: 92,
+            })
+        );
+    }
+
+    #[test]
+    fn test_filter_drops_unselected_blocks() {
+        let mut stream = TestMsg::QualityInd.bytes();
+        stream.extend(TestMsg::Dop.bytes());
+
+        let mut parser = SbfParser::new().with_filter(BlockFilter::none().allow_type::<DOP>());
+        let mut events = Vec::new();
+        let mut input: &[u8] = &stream;
+        while let Some(event) = parser.consume_event(input) {
+            events.push(event);
+            input = &[];
+        }
+        assert!(matches!(
+            events[0],
+            SbfEvent::Filtered {
+                block_id: 4082,
+                offset: 0
+            }
+        ));
+        assert!(matches!(&events[1], SbfEvent::Message(m) if TestMsg::Dop.matches(m)));
+        assert_eq!(events.len(), 2);
+        assert_eq!(parser.stats().blocks_filtered, 1);
+        assert_eq!(parser.stats().bytes_skipped, 0);
+
+        let filter = BlockFilter::none().allow_type::<DOP>().keep_raw(true);
+        let mut parser = SbfParser::new().with_filter(filter);
+        match parser.consume(&stream) {
+            Some(Messages::RawBlock(raw)) => assert_eq!(raw.id, 4082),
+            other => panic!("expected QualityInd kept raw, got {other:?}"),
+        }
+        assert!(matches!(parser.consume(&[]), Some(Messages::DOP(_))));
+    }
+
+    #[test]
+    fn test_parse_datagram_filtered() {
+        let quality_ind = TestMsg::QualityInd.bytes();
+        let filter = BlockFilter::none().allow(4001);
+        assert!(matches!(
+            parse_datagram_filtered(&quality_ind, &filter),
+            Ok(None)
+        ));
+        assert!(matches!(
+            parse_datagram_filtered(&TestMsg::Dop.bytes(), &filter),
+            Ok(Some(Messages::DOP(_)))
+        ));
+        assert!(matches!(
+            parse_datagram_filtered(&quality_ind, &BlockFilter::all()),
+            Ok(Some(Messages::QualityInd(_)))
+        ));
+
+        let mut bad_crc = quality_ind.clone();
+        bad_crc[2] ^= 0xFF;
+        assert_eq!(
+            parse_datagram_filtered(&bad_crc, &filter).unwrap_err(),
+            DatagramError::InvalidCrc
+        );
+    }
+
+    #[test]
+    fn test_parse_datagrams_advances_past_errors() {
+        let mut bad_crc = TestMsg::Dop.bytes();
+        bad_crc[2] ^= 0xFF;
+        let mut bad_payload = build_sbf_message(4082, &[0u8; 8]);
+        // A QualityInd claiming more indicators than its body holds.
+        bad_payload[14] = 20;
+        let crc = State::<XMODEM>::calculate(&bad_payload[4..]);
+        bad_payload[2..4].copy_from_slice(&crc.to_le_bytes());
+
+        let mut datagram = TestMsg::QualityInd.bytes();
+        let bad_crc_at = datagram.len();
+        datagram.extend_from_slice(&bad_crc);
+        let bad_payload_at = datagram.len();
+        datagram.extend_from_slice(&bad_payload);
+        datagram.extend_from_slice(b"junk");
+        let dop_at = datagram.len();
+        datagram.extend(TestMsg::Dop.bytes());
+        let truncated_at = datagram.len();
+        datagram.extend_from_slice(&TestMsg::QualityInd.bytes()[..20]);
+
+        let mut blocks = parse_datagrams(&datagram);
+        let mut seen = Vec::new();
+        while let Some(result) = blocks.next() {
+            seen.push((blocks.offset(), result));
+        }
+
+        assert_eq!(seen.len(), 5);
+        assert!(matches!(&seen[0], (0, Ok(m)) if TestMsg::QualityInd.matches(m)));
+        assert_eq!(seen[1].0, bad_crc_at);
+        assert!(matches!(seen[1].1, Err(DatagramError::InvalidCrc)));
+        assert_eq!(seen[2].0, bad_payload_at);
+        assert!(matches!(seen[2].1, Err(DatagramError::InvalidPayload)));
+        assert_eq!(seen[3].0, dop_at);
+        assert!(matches!(&seen[3].1, Ok(m) if TestMsg::Dop.matches(m)));
+        assert_eq!(seen[4].0, truncated_at);
+        assert!(matches!(seen[4].1, Err(DatagramError::Incomplete)));
+    }
+
+    #[test]
+    fn test_parse_datagrams_without_sync() {
+        let mut blocks = parse_datagrams(b"no blocks here");
+        assert!(matches!(blocks.next(), Some(Err(DatagramError::NoSync))));
+        assert!(blocks.next().is_none());
+        assert!(parse_datagrams(&[]).next().is_none());
+    }
+
+    /// Feed `stream` in `chunk`-sized pieces, draining messages after each.
+    fn consume_chunked<B: ParserBuffer>(
+        parser: &mut SbfParser<B>,
+        stream: &[u8],
+        chunk: usize,
+    ) -> Vec<Messages> {
+        let mut messages = Vec::new();
+        for piece in stream.chunks(chunk) {
+            let mut input = piece;
+            while let Some(msg) = parser.consume(input) {
+                messages.push(msg);
+                input = &[];
+            }
+        }
+        messages
+    }
+
+    #[test]
+    fn test_fixed_capacity_parsers() {
+        let mut stream = Vec::new();
+        for msg in [TestMsg::QualityInd, TestMsg::ReceiverSetup, TestMsg::Dop] {
+            stream.extend_from_slice(b"noise");
+            stream.extend(msg.bytes());
+        }
+
+        let mut fixed = FixedSbfParser::<512>::default();
+        let messages = consume_chunked(&mut fixed, &stream, 64);
+        assert_eq!(messages.len(), 3);
+        assert!(TestMsg::ReceiverSetup.matches(&messages[1]));
+
+        let mut storage = [0u8; 512];
+        let mut borrowed = SbfParser::with_buffer(SliceBuffer::new(&mut storage));
+        let messages = consume_chunked(&mut borrowed, &stream, 64);
+        assert_eq!(messages.len(), 3);
+        assert!(TestMsg::Dop.matches(&messages[2]));
+        assert_eq!(borrowed.stats(), fixed.stats());
+    }
+
+    #[test]
+    fn test_fixed_capacity_parser_drops_oldest_data() {
+        let valid = build_sbf_message(4082, VALID_QUALITY_IND_PAYLOAD);
+        let receiver_setup = create_valid_receiver_setup_message();
+
+        // A block longer than the buffer can never complete.
+        let mut parser = FixedSbfParser::<64>::default();
+        let mut stream = receiver_setup.clone();
+        stream.extend_from_slice(&valid);
+        let messages = consume_chunked(&mut parser, &stream, 16);
+        assert!(matches!(messages[..], [Messages::QualityInd(_)]));
+        assert_eq!(parser.stats().header_errors, 1);
+
+        // Input larger than the buffer keeps only its newest bytes.
+        let mut parser = FixedSbfParser::<64>::default();
+        let mut stream = vec![0u8; 100];
+        stream.extend_from_slice(&valid);
+        assert!(matches!(
+            parser.consume(&stream),
+            Some(Messages::QualityInd(_))
+        ));
+        assert_eq!(parser.stats().bytes_skipped, 100);
+    }
+
+    #[test]
+    fn test_fixed_buffers_reject_overflowing_append() {
+        let mut fixed = heapless::Vec::<u8, 4>::new();
+        assert_eq!(fixed.append(b"$@$"), Ok(()));
+        assert_eq!(fixed.append(b"@@"), Err(BufferFull));
+        assert_eq!(fixed.as_slice(), b"$@$");
+
+        let mut storage = [0u8; 4];
+        let mut borrowed = SliceBuffer::new(&mut storage);
+        assert_eq!(borrowed.append(b"$@$"), Ok(()));
+        assert_eq!(borrowed.append(b"@@"), Err(BufferFull));
+        assert_eq!(borrowed.as_slice(), b"$@$");
+    }
+
     proptest! {
 
         #[test]
@@ -651,7 +2071,7 @@ mod tests {
                     prop_assert_eq!(setup.tow, Some(490403000));
                     prop_assert_eq!(setup.wnc, Some(2360));
                     prop_assert_eq!(&setup.marker_name[..11], b"TEST_MARKER");
-                    prop_assert_eq!(setup.monument_idx, 1);
+                    prop_assert_eq!(setup.monument_idx, Some(1));
                 },
                 Some(other) => {
                     prop_assert!(false, "Parsed to wrong message type: {:?}", other);
@@ -1,7 +1,9 @@
-use crate::parser::SbfParser;
-use crate::Messages;
+use crate::parser::{BlockFilter, Framed, ParserStats, SbfEvent, SbfParser};
+use crate::{Messages, SbfBlock};
 
 use std::io::Read;
+use std::marker::PhantomData;
+use std::time::Instant;
 
 // NOTE: May make this tunable. The std reader is going to be on user
 // space linux and in many cases users will have the memory.
@@ -31,6 +33,8 @@ pub struct SbfReader<R: Read> {
     reader: R,
     parser: SbfParser,
     drain_internal: bool,
+    /// When the last read that returned data did so.
+    read_at: Option<Instant>,
 }
 
 impl<R: Read> SbfReader<R> {
@@ -39,14 +43,66 @@ impl<R: Read> SbfReader<R> {
             reader,
             parser: SbfParser::new(),
             drain_internal: false,
+            read_at: None,
         }
     }
-}
 
-impl<R: Read> Iterator for SbfReader<R> {
-    type Item = Result<Messages, std::io::Error>;
+    /// Only decode the blocks `filter` selects.
+    pub fn with_filter(mut self, filter: BlockFilter) -> Self {
+        self.parser = self.parser.with_filter(filter);
+        self
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Counters for everything the underlying parser has consumed so far.
+    pub fn stats(&self) -> ParserStats {
+        self.parser.stats()
+    }
+
+    /// Turn the reader into an iterator over parser events, which reports
+    /// discarded data (CRC, header and payload errors, skipped bytes) along
+    /// with the messages.
+    pub fn events(self) -> SbfEvents<R> {
+        SbfEvents { inner: self }
+    }
+
+    /// Turn the reader into an iterator over [`Framed`] messages, which
+    /// carry the stream offset of each block and the time the read that
+    /// completed it returned.
+    pub fn framed(self) -> SbfFramed<R> {
+        SbfFramed { inner: self }
+    }
+
+    /// Turn the reader into an iterator over the `T` blocks alone. Only `T`
+    /// is decoded: this replaces any filter set with
+    /// [`SbfReader::with_filter`].
+    ///
+    /// ```no_run
+    /// use libsbf::reader::SbfReader;
+    /// use libsbf::PVTGeodetic;
+    /// use std::fs::File;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     for pvt in SbfReader::new(File::open("log.sbf")?).only::<PVTGeodetic>() {
+    ///         println!("{:?}", pvt?.latitude);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn only<T: SbfBlock>(mut self) -> SbfOnly<R, T> {
+        self.parser = self
+            .parser
+            .with_filter(BlockFilter::none().allow_type::<T>());
+        SbfOnly {
+            inner: self,
+            block: PhantomData,
+        }
+    }
+
+    /// Read from the reader until `parse` yields an item, or EOF.
+    fn next_with<T>(
+        &mut self,
+        mut parse: impl FnMut(&mut SbfParser, &[u8]) -> Option<T>,
+    ) -> Option<Result<T, std::io::Error>> {
         let mut buffer = [0u8; BUFFER_SIZE];
         loop {
             tracing::debug!("Trying to read from reader");
@@ -57,6 +113,9 @@ impl<R: Read> Iterator for SbfReader<R> {
                     match self.reader.read(&mut buffer) {
                         Ok(br) => {
                             tracing::debug!("Successfully read {br} bytes from reader");
+                            if br > 0 {
+                                self.read_at = Some(Instant::now());
+                            }
                             (br, br == 0)
                         }
                         Err(e) => {
@@ -66,7 +125,7 @@ impl<R: Read> Iterator for SbfReader<R> {
                 }
             };
 
-            match self.parser.consume(&buffer[..bytes_read]) {
+            match parse(&mut self.parser, &buffer[..bytes_read]) {
                 Some(msg) => {
                     // NOTE: When we get a message the parser still
                     // contains the internal buffer so lets drain that
@@ -93,10 +152,93 @@ impl<R: Read> Iterator for SbfReader<R> {
     }
 }
 
+impl<R: Read> Iterator for SbfReader<R> {
+    type Item = Result<Messages, std::io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(SbfParser::consume)
+    }
+}
+
+/// Iterator over the [`SbfEvent`]s of an [`SbfReader`], created by
+/// [`SbfReader::events`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub struct SbfEvents<R: Read> {
+    inner: SbfReader<R>,
+}
+
+impl<R: Read> SbfEvents<R> {
+    /// Counters for everything the underlying parser has consumed so far.
+    pub fn stats(&self) -> ParserStats {
+        self.inner.stats()
+    }
+}
+
+impl<R: Read> Iterator for SbfEvents<R> {
+    type Item = Result<SbfEvent, std::io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_with(SbfParser::consume_event)
+    }
+}
+
+/// Iterator over the [`Framed`] messages of an [`SbfReader`], created by
+/// [`SbfReader::framed`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub struct SbfFramed<R: Read> {
+    inner: SbfReader<R>,
+}
+
+impl<R: Read> SbfFramed<R> {
+    /// Counters for everything the underlying parser has consumed so far.
+    pub fn stats(&self) -> ParserStats {
+        self.inner.stats()
+    }
+}
+
+impl<R: Read> Iterator for SbfFramed<R> {
+    type Item = Result<Framed, std::io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut item = self.inner.next_with(SbfParser::consume_framed);
+        // Buffered blocks are drained before the next read, so the last read
+        // is the one that completed this block.
+        if let Some(Ok(framed)) = &mut item {
+            framed.host_time = self.inner.read_at;
+        }
+        item
+    }
+}
+
+/// Iterator over the `T` blocks of an [`SbfReader`], created by
+/// [`SbfReader::only`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub struct SbfOnly<R: Read, T> {
+    inner: SbfReader<R>,
+    block: PhantomData<fn() -> T>,
+}
+
+impl<R: Read, T> SbfOnly<R, T> {
+    /// Counters for everything the underlying parser has consumed so far.
+    pub fn stats(&self) -> ParserStats {
+        self.inner.stats()
+    }
+}
+
+impl<R: Read, T: SbfBlock> Iterator for SbfOnly<R, T> {
+    type Item = Result<T, std::io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // The filter only lets `T` through.
+        self.inner
+            .next_with(|parser, input| parser.consume(input)?.downcast().ok())
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::{reader::SbfReader, Messages};
     use anyhow::Result;
-    use libsbf::{reader::SbfReader, Messages};
     use std::io::{BufRead, Read};
 
     #[test]
@@ -0,0 +1,234 @@
+//! Satellite IDs decoded from SBF SVID numbers.
+//!
+//! SBF blocks identify satellites by a single SVID byte, with a range of
+//! numbers per constellation. [`SatelliteId`] splits it into the
+//! constellation and the satellite's PRN or slot number, and prints the
+//! RINEX form.
+//!
+//! ```
+//! use libsbf::satellite::{Constellation, SatelliteId};
+//!
+//! let sat = SatelliteId::try_from(81).unwrap();
+//! assert_eq!(sat.constellation(), Constellation::Galileo);
+//! assert_eq!(sat.prn(), 11);
+//! assert_eq!(sat.to_string(), "E11");
+//! assert_eq!(u8::from(sat), 81);
+//! ```
+
+use crate::messages::{
+    BDSIon, ChannelSatInfo, GALGstGps, GALIon, GALNav, GALUtc, GEONav, GEORawL1, GPSCNav, GPSIon,
+    GPSNav, GPSUtc, MeasEpochChannelType1, SatInfo,
+};
+use crate::view::MeasEpochChannelRef;
+
+use core::fmt;
+
+/// A satellite navigation system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Constellation {
+    Gps,
+    Glonass,
+    Galileo,
+    Sbas,
+    BeiDou,
+    Qzss,
+    NavIC,
+}
+
+impl Constellation {
+    /// The RINEX system letter, e.g. `'G'` for GPS.
+    pub fn rinex_code(&self) -> char {
+        match self {
+            Constellation::Gps => 'G',
+            Constellation::Glonass => 'R',
+            Constellation::Galileo => 'E',
+            Constellation::Sbas => 'S',
+            Constellation::BeiDou => 'C',
+            Constellation::Qzss => 'J',
+            Constellation::NavIC => 'I',
+        }
+    }
+}
+
+impl fmt::Display for Constellation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Constellation::Gps => "GPS",
+            Constellation::Glonass => "GLONASS",
+            Constellation::Galileo => "Galileo",
+            Constellation::Sbas => "SBAS",
+            Constellation::BeiDou => "BeiDou",
+            Constellation::Qzss => "QZSS",
+            Constellation::NavIC => "NavIC",
+        };
+        f.write_str(name)
+    }
+}
+
+/// An SVID that names no satellite: 0, an unknown GLONASS slot (62), an
+/// L-band satellite (107-119) or a reserved number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidSvid(pub u8);
+
+/// A satellite: its constellation and PRN, or slot number for GLONASS. See
+/// the [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SatelliteId {
+    constellation: Constellation,
+    prn: u8,
+}
+
+impl SatelliteId {
+    /// The satellite `prn` of `constellation`, or None if SBF has no SVID for
+    /// it. SBAS satellites keep their full PRN, 120 to 158.
+    pub fn new(constellation: Constellation, prn: u8) -> Option<Self> {
+        let valid = match constellation {
+            Constellation::Gps => (1..=37).contains(&prn),
+            Constellation::Glonass => (1..=30).contains(&prn),
+            Constellation::Galileo => (1..=36).contains(&prn),
+            Constellation::Sbas => (120..=158).contains(&prn),
+            Constellation::BeiDou => (1..=63).contains(&prn),
+            Constellation::Qzss => (1..=10).contains(&prn),
+            Constellation::NavIC => (1..=14).contains(&prn),
+        };
+        valid.then_some(Self { constellation, prn })
+    }
+
+    pub fn constellation(&self) -> Constellation {
+        self.constellation
+    }
+
+    /// PRN number, or slot number for GLONASS.
+    pub fn prn(&self) -> u8 {
+        self.prn
+    }
+}
+
+impl TryFrom<u8> for SatelliteId {
+    type Error = InvalidSvid;
+
+    fn try_from(svid: u8) -> Result<Self, InvalidSvid> {
+        let (constellation, prn) = match svid {
+            1..=37 => (Constellation::Gps, svid),
+            38..=61 => (Constellation::Glonass, svid - 37),
+            63..=68 => (Constellation::Glonass, svid - 38),
+            71..=106 => (Constellation::Galileo, svid - 70),
+            120..=140 => (Constellation::Sbas, svid),
+            141..=180 => (Constellation::BeiDou, svid - 140),
+            181..=190 => (Constellation::Qzss, svid - 180),
+            191..=197 => (Constellation::NavIC, svid - 190),
+            198..=215 => (Constellation::Sbas, svid - 57),
+            216..=222 => (Constellation::NavIC, svid - 208),
+            223..=245 => (Constellation::BeiDou, svid - 182),
+            _ => return Err(InvalidSvid(svid)),
+        };
+        Ok(Self { constellation, prn })
+    }
+}
+
+impl From<SatelliteId> for u8 {
+    fn from(sat: SatelliteId) -> u8 {
+        let prn = sat.prn;
+        match sat.constellation {
+            Constellation::Gps => prn,
+            Constellation::Glonass if prn <= 24 => prn + 37,
+            Constellation::Glonass => prn + 38,
+            Constellation::Galileo => prn + 70,
+            Constellation::Sbas if prn <= 140 => prn,
+            Constellation::Sbas => prn + 57,
+            Constellation::BeiDou if prn <= 40 => prn + 140,
+            Constellation::BeiDou => prn + 182,
+            Constellation::Qzss => prn + 180,
+            Constellation::NavIC if prn <= 7 => prn + 190,
+            Constellation::NavIC => prn + 208,
+        }
+    }
+}
+
+/// RINEX form: system letter and two-digit number, e.g. `G05`. SBAS
+/// satellites are numbered from PRN 100, so PRN 120 is `S20`.
+impl fmt::Display for SatelliteId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let number = match self.constellation {
+            Constellation::Sbas => self.prn - 100,
+            _ => self.prn,
+        };
+        write!(f, "{}{:02}", self.constellation.rinex_code(), number)
+    }
+}
+
+macro_rules! impl_satellite {
+    ($($block:ty => $field:ident,)+) => {
+        $(
+            impl $block {
+                /// The satellite named by the SVID, or None if it names
+                /// none.
+                pub fn satellite(&self) -> Option<SatelliteId> {
+                    SatelliteId::try_from(self.$field).ok()
+                }
+            }
+        )+
+    };
+}
+
+impl_satellite!(
+    BDSIon => prn,
+    ChannelSatInfo => svid,
+    GALGstGps => svid,
+    GALIon => svid,
+    GALNav => svid,
+    GALUtc => svid,
+    GEONav => prn,
+    GEORawL1 => svid,
+    GPSCNav => prn,
+    GPSIon => prn,
+    GPSNav => prn,
+    GPSUtc => prn,
+    MeasEpochChannelType1 => svid,
+    SatInfo => svid,
+);
+
+impl MeasEpochChannelRef<'_> {
+    /// The satellite named by the SVID, or None if it names none.
+    pub fn satellite(&self) -> Option<SatelliteId> {
+        SatelliteId::try_from(self.svid()).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Constellation, InvalidSvid, SatelliteId};
+
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_svid_round_trips() {
+        for svid in 0..=u8::MAX {
+            match SatelliteId::try_from(svid) {
+                Ok(sat) => {
+                    assert_eq!(u8::from(sat), svid, "{sat}");
+                    assert_eq!(SatelliteId::new(sat.constellation(), sat.prn()), Some(sat));
+                }
+                Err(e) => assert_eq!(e, InvalidSvid(svid)),
+            }
+        }
+        assert!(SatelliteId::try_from(62).is_err());
+        assert!(SatelliteId::try_from(110).is_err());
+        assert_eq!(SatelliteId::new(Constellation::Gps, 38), None);
+    }
+
+    #[test]
+    fn test_svid_display() {
+        let rinex = |svid: u8| SatelliteId::try_from(svid).unwrap().to_string();
+        assert_eq!(rinex(5), "G05");
+        assert_eq!(rinex(38), "R01");
+        assert_eq!(rinex(68), "R30");
+        assert_eq!(rinex(81), "E11");
+        assert_eq!(rinex(123), "S23");
+        assert_eq!(rinex(198), "S41");
+        assert_eq!(rinex(160), "C20");
+        assert_eq!(rinex(245), "C63");
+        assert_eq!(rinex(183), "J03");
+        assert_eq!(rinex(216), "I08");
+    }
+}
@@ -0,0 +1,338 @@
+//! Signal types decoded from the SBF signal number.
+//!
+//! Measurement blocks pack the signal number and the antenna into their
+//! `Type` field: bits 0-4 are the signal number, or 31 if it is 32 or more,
+//! in which case bits 3-7 of `ObsInfo` hold the rest. Bits 5-7 are the
+//! antenna. For GLONASS FDMA signals, bits 3-7 of a Type1 `ObsInfo` are
+//! instead the frequency number plus 8; [`crate::ChannelStateInfo`] already
+//! gives the antenna as a plain field.
+//!
+//! ```
+//! use libsbf::signal::SignalType;
+//!
+//! let signal = SignalType::from(20);
+//! assert_eq!(signal, SignalType::GalE5a);
+//! assert_eq!(signal.rinex_code(), Some("5Q"));
+//! assert_eq!(signal.carrier_frequency(None), Some(1176.45e6));
+//! // GLONASS L1 C/A on frequency channel -7, frequency number 1.
+//! assert_eq!(SignalType::GloL1CA.carrier_frequency(Some(1)), Some(1598.0625e6));
+//! ```
+
+use crate::messages::{MeasEpochChannelType1, MeasEpochChannelType2, MeasExtraChannelSub};
+use crate::satellite::Constellation;
+use crate::view::{MeasEpochChannelRef, MeasEpochSignalRef};
+
+use core::fmt;
+use num_enum::{FromPrimitive, IntoPrimitive};
+
+const L1: f64 = 1575.42e6;
+const L2: f64 = 1227.60e6;
+const L5: f64 = 1176.45e6;
+const E6: f64 = 1278.75e6;
+const E5B: f64 = 1207.14e6;
+const E5: f64 = 1191.795e6;
+const B1I: f64 = 1561.098e6;
+const B3I: f64 = 1268.52e6;
+const G3: f64 = 1202.025e6;
+/// GLONASS FDMA carriers: channel 0 and spacing per channel.
+const G1: (f64, f64) = (1602.0e6, 0.5625e6);
+const G2: (f64, f64) = (1246.0e6, 0.4375e6);
+
+/// A signal, by its SBF signal number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, FromPrimitive, IntoPrimitive)]
+#[repr(u8)]
+pub enum SignalType {
+    GpsL1CA = 0,
+    GpsL1P = 1,
+    GpsL2P = 2,
+    GpsL2C = 3,
+    GpsL5 = 4,
+    GpsL1C = 5,
+    QzssL1CA = 6,
+    QzssL2C = 7,
+    GloL1CA = 8,
+    GloL1P = 9,
+    GloL2P = 10,
+    GloL2CA = 11,
+    GloL3 = 12,
+    BdsB1C = 13,
+    BdsB2a = 14,
+    NavicL5 = 15,
+    GalE1 = 17,
+    GalE6 = 19,
+    GalE5a = 20,
+    GalE5b = 21,
+    GalE5AltBoc = 22,
+    SbasL1 = 24,
+    SbasL5 = 25,
+    QzssL5 = 26,
+    BdsB1I = 28,
+    BdsB2I = 29,
+    BdsB3I = 30,
+    QzssL1C = 32,
+    QzssL1S = 33,
+    BdsB2b = 34,
+    /// A signal number not listed above.
+    #[num_enum(catch_all)]
+    Unknown(u8),
+}
+
+impl SignalType {
+    pub fn constellation(&self) -> Option<Constellation> {
+        use SignalType::*;
+        Some(match self {
+            GpsL1CA | GpsL1P | GpsL2P | GpsL2C | GpsL5 | GpsL1C => Constellation::Gps,
+            GloL1CA | GloL1P | GloL2P | GloL2CA | GloL3 => Constellation::Glonass,
+            GalE1 | GalE6 | GalE5a | GalE5b | GalE5AltBoc => Constellation::Galileo,
+            SbasL1 | SbasL5 => Constellation::Sbas,
+            BdsB1C | BdsB2a | BdsB1I | BdsB2I | BdsB3I | BdsB2b => Constellation::BeiDou,
+            QzssL1CA | QzssL2C | QzssL5 | QzssL1C | QzssL1S => Constellation::Qzss,
+            NavicL5 => Constellation::NavIC,
+            Unknown(_) => return None,
+        })
+    }
+
+    /// Whether the carrier frequency depends on the satellite's GLONASS
+    /// frequency number.
+    pub fn is_fdma(&self) -> bool {
+        use SignalType::*;
+        matches!(self, GloL1CA | GloL1P | GloL2P | GloL2CA)
+    }
+
+    /// Carrier frequency in Hz. GLONASS FDMA signals need the satellite's
+    /// frequency number `freq_nr`, which SBF gives with an offset of 8, e.g.
+    /// `ChannelSatInfo::freq_nr`; other signals ignore it.
+    pub fn carrier_frequency(&self, freq_nr: Option<u8>) -> Option<f64> {
+        use SignalType::*;
+        let fdma = |(base, spacing): (f64, f64)| {
+            let channel = f64::from(freq_nr?) - 8.0;
+            Some(base + channel * spacing)
+        };
+        Some(match self {
+            GpsL1CA | GpsL1P | GpsL1C | QzssL1CA | QzssL1C | QzssL1S | GalE1 | SbasL1 | BdsB1C => {
+                L1
+            }
+            GpsL2P | GpsL2C | QzssL2C => L2,
+            GpsL5 | QzssL5 | GalE5a | SbasL5 | BdsB2a | NavicL5 => L5,
+            GloL1CA | GloL1P => return fdma(G1),
+            GloL2P | GloL2CA => return fdma(G2),
+            GloL3 => G3,
+            GalE6 => E6,
+            GalE5b | BdsB2I | BdsB2b => E5B,
+            GalE5AltBoc => E5,
+            BdsB1I => B1I,
+            BdsB3I => B3I,
+            Unknown(_) => return None,
+        })
+    }
+
+    /// The RINEX 3 observation code: band and attribute, e.g. `"1C"`.
+    pub fn rinex_code(&self) -> Option<&'static str> {
+        use SignalType::*;
+        Some(match self {
+            GpsL1CA | QzssL1CA | GloL1CA | GalE1 | SbasL1 => "1C",
+            GpsL1P => "1W",
+            GpsL2P => "2W",
+            GpsL2C | QzssL2C => "2L",
+            GpsL5 | QzssL5 => "5Q",
+            GpsL1C | QzssL1C => "1L",
+            QzssL1S => "1Z",
+            GloL1P => "1P",
+            GloL2P => "2P",
+            GloL2CA => "2C",
+            GloL3 => "3Q",
+            BdsB1C => "1P",
+            BdsB2a => "5P",
+            BdsB1I => "2I",
+            BdsB2I => "7I",
+            BdsB3I => "6I",
+            BdsB2b => "7D",
+            NavicL5 => "5A",
+            GalE6 => "6C",
+            GalE5a => "5Q",
+            GalE5b => "7Q",
+            GalE5AltBoc => "8Q",
+            SbasL5 => "5I",
+            Unknown(_) => return None,
+        })
+    }
+}
+
+impl fmt::Display for SignalType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use SignalType::*;
+        let name = match self {
+            GpsL1CA => "GPS L1 C/A",
+            GpsL1P => "GPS L1 P",
+            GpsL2P => "GPS L2 P",
+            GpsL2C => "GPS L2C",
+            GpsL5 => "GPS L5",
+            GpsL1C => "GPS L1C",
+            QzssL1CA => "QZSS L1 C/A",
+            QzssL2C => "QZSS L2C",
+            GloL1CA => "GLONASS L1 C/A",
+            GloL1P => "GLONASS L1 P",
+            GloL2P => "GLONASS L2 P",
+            GloL2CA => "GLONASS L2 C/A",
+            GloL3 => "GLONASS L3",
+            BdsB1C => "BeiDou B1C",
+            BdsB2a => "BeiDou B2a",
+            NavicL5 => "NavIC L5",
+            GalE1 => "Galileo E1",
+            GalE6 => "Galileo E6",
+            GalE5a => "Galileo E5a",
+            GalE5b => "Galileo E5b",
+            GalE5AltBoc => "Galileo E5 AltBOC",
+            SbasL1 => "SBAS L1",
+            SbasL5 => "SBAS L5",
+            QzssL5 => "QZSS L5",
+            BdsB1I => "BeiDou B1I",
+            BdsB2I => "BeiDou B2I",
+            BdsB3I => "BeiDou B3I",
+            QzssL1C => "QZSS L1C",
+            QzssL1S => "QZSS L1S",
+            BdsB2b => "BeiDou B2b",
+            Unknown(x) => return write!(f, "Unknown({x})"),
+        };
+        f.write_str(name)
+    }
+}
+
+/// The signal number packed in a `Type` field and its `ObsInfo` field.
+fn signal_number(type_field: u8, obs_info: u8) -> u8 {
+    match type_field & 0x1F {
+        31 => (obs_info >> 3) + 32,
+        sig => sig,
+    }
+}
+
+macro_rules! impl_signal {
+    ($($t:ty => |$s:ident| ($type_field:expr, $obs_info:expr),)+) => {
+        $(
+            impl $t {
+                /// SBF signal number.
+                pub fn signal_number(&self) -> u8 {
+                    let $s = self;
+                    signal_number($type_field, $obs_info)
+                }
+
+                pub fn signal_type(&self) -> SignalType {
+                    SignalType::from(self.signal_number())
+                }
+
+                /// Antenna the signal was tracked on, 0 for the main antenna.
+                pub fn antenna(&self) -> u8 {
+                    let $s = self;
+                    $type_field >> 5
+                }
+            }
+        )+
+    };
+}
+
+impl_signal!(
+    MeasEpochChannelType1 => |ch| (ch.type_field, ch.obs_info),
+    MeasEpochChannelType2 => |sig| (sig.type_field, sig.obs_info),
+    MeasEpochChannelRef<'_> => |ch| (ch.type_field(), ch.obs_info()),
+    MeasEpochSignalRef<'_> => |sig| (sig.type_field(), sig.obs_info()),
+);
+
+macro_rules! impl_channel_frequency {
+    ($($t:ty => |$s:ident| $obs_info:expr,)+) => {
+        $(
+            impl $t {
+                /// GLONASS frequency number with an offset of 8, if the
+                /// channel's main signal is a GLONASS FDMA signal.
+                pub fn glonass_freq_nr(&self) -> Option<u8> {
+                    let $s = self;
+                    self.signal_type().is_fdma().then_some($obs_info >> 3)
+                }
+
+                /// Carrier frequency in Hz of the main signal, see
+                /// [`SignalType::carrier_frequency`].
+                pub fn carrier_frequency(&self) -> Option<f64> {
+                    self.signal_type().carrier_frequency(self.glonass_freq_nr())
+                }
+            }
+        )+
+    };
+}
+
+impl_channel_frequency!(
+    MeasEpochChannelType1 => |ch| ch.obs_info,
+    MeasEpochChannelRef<'_> => |ch| ch.obs_info(),
+);
+
+macro_rules! impl_signal_frequency {
+    ($($t:ty,)+) => {
+        $(
+            impl $t {
+                /// Carrier frequency in Hz. Type2 sub-blocks do not carry the
+                /// GLONASS frequency number: pass the Type1 channel's
+                /// `glonass_freq_nr()`.
+                pub fn carrier_frequency(&self, freq_nr: Option<u8>) -> Option<f64> {
+                    self.signal_type().carrier_frequency(freq_nr)
+                }
+            }
+        )+
+    };
+}
+
+impl_signal_frequency!(MeasEpochChannelType2, MeasEpochSignalRef<'_>,);
+
+impl MeasExtraChannelSub {
+    /// SBF signal number. MeasExtra has no room for signal numbers above 30:
+    /// those all read as 31.
+    pub fn signal_number(&self) -> u8 {
+        self.type_field & 0x1F
+    }
+
+    pub fn signal_type(&self) -> SignalType {
+        SignalType::from(self.signal_number())
+    }
+
+    /// Antenna the signal was tracked on, 0 for the main antenna.
+    pub fn antenna(&self) -> u8 {
+        self.type_field >> 5
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{signal_number, SignalType};
+    use crate::satellite::Constellation;
+
+    #[test]
+    fn test_signal_numbers() {
+        for sig in 0..=u8::MAX {
+            let signal = SignalType::from(sig);
+            assert_eq!(u8::from(signal), sig);
+            let known = !matches!(signal, SignalType::Unknown(_));
+            assert_eq!(signal.constellation().is_some(), known, "{signal}");
+            assert_eq!(signal.rinex_code().is_some(), known, "{signal}");
+            assert_eq!(
+                signal.carrier_frequency(Some(8)).is_some(),
+                known,
+                "{signal}"
+            );
+            assert_eq!(
+                signal.carrier_frequency(None).is_some(),
+                known && !signal.is_fdma()
+            );
+        }
+        assert_eq!(
+            SignalType::from(29).constellation(),
+            Some(Constellation::BeiDou)
+        );
+
+        // Antenna 2, GPS L2C.
+        assert_eq!(signal_number(0x43, 0xFF), 3);
+        // Signal 33 is QZSS L1S.
+        assert_eq!(signal_number(31, 1 << 3), 33);
+        // GLONASS L2 C/A on frequency channel +6.
+        assert_eq!(
+            SignalType::GloL2CA.carrier_frequency(Some(14)),
+            Some(1248.625e6)
+        );
+    }
+}
@@ -0,0 +1,69 @@
+//! Blocks shared by the unit tests.
+
+use alloc::vec::Vec;
+
+/// QualityInd block 4082 at week 2360, TOW 490403000, as used by the parser
+/// tests.
+pub(crate) const VALID_QUALITY_IND: &[u8; 32] = &[
+    36, 64, 134, 98, 242, 15, 32, 0, 184, 244, 58, 29, 56, 9, 7, 0, 11, 10, 12, 10, 1, 0, 2, 0, 21,
+    10, 31, 0, 0, 0, 0, 0,
+];
+
+/// Frame `body` as an SBF block with ID `block_id`, zero-padding it to a
+/// multiple of 4 bytes.
+pub(crate) fn sbf_block(block_id: u16, body: &[u8]) -> Vec<u8> {
+    let len = (8 + body.len()).next_multiple_of(4);
+    let mut frame = b"$@\0\0".to_vec();
+    frame.extend_from_slice(&block_id.to_le_bytes());
+    frame.extend_from_slice(&(len as u16).to_le_bytes());
+    frame.extend_from_slice(body);
+    frame.resize(len, 0);
+    let crc = crc16::State::<crc16::XMODEM>::calculate(&frame[4..]);
+    frame[2..4].copy_from_slice(&crc.to_le_bytes());
+    frame
+}
+
+/// An unsupported block 4999, longer than a UDP datagram can carry.
+pub(crate) fn long_block() -> Vec<u8> {
+    sbf_block(4999, &[0; 65532 - 8])
+}
+
+/// A MeasEpoch Rev 1 block as a receiver sends it.
+pub(crate) fn meas_epoch_block() -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&490403000u32.to_le_bytes());
+    body.extend_from_slice(&2360u16.to_le_bytes());
+    // N1, SB1Length, SB2Length, CommonFlags, CumClkJumps, Reserved.
+    body.extend_from_slice(&[2, 20, 12, 0, 0, 0]);
+
+    // GPS PRN 5 L1 C/A: pseudorange 2^32 + 0x2345_6789 mm, Doppler
+    // -1234.5678 Hz, C/N0 50 dB-Hz.
+    body.extend_from_slice(&[1, 0, 5, 0x01]);
+    body.extend_from_slice(&0x2345_6789u32.to_le_bytes());
+    body.extend_from_slice(&(-12_345_678i32).to_le_bytes());
+    body.extend_from_slice(&1000u16.to_le_bytes());
+    body.extend_from_slice(&[(-2i8) as u8, 160]);
+    body.extend_from_slice(&300u16.to_le_bytes());
+    body.extend_from_slice(&[0x01, 1]);
+    // GPS L5: code offset -1 mm, Doppler offset 13.1172 Hz, C/N0 40
+    // dB-Hz.
+    body.extend_from_slice(&[4, 12, 120, 0x07 | 0x02 << 3, 1, 0x04]);
+    body.extend_from_slice(&0xFFFFu16.to_le_bytes());
+    body.extend_from_slice(&500u16.to_le_bytes());
+    body.extend_from_slice(&100u16.to_le_bytes());
+
+    // GLONASS slot 1 on frequency channel +1, L1 C/A: pseudorange
+    // 20000 km, Doppler 100 Hz, C/N0 40 dB-Hz.
+    body.extend_from_slice(&[2, 8, 38, 0x04]);
+    body.extend_from_slice(&0xA817_C800u32.to_le_bytes());
+    body.extend_from_slice(&1_000_000i32.to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes());
+    body.extend_from_slice(&[0, 120]);
+    body.extend_from_slice(&60u16.to_le_bytes());
+    body.extend_from_slice(&[9 << 3, 1]);
+    // GLONASS L2 C/A with no offsets, C/N0 36 dB-Hz.
+    body.extend_from_slice(&[11, 60, 104, 0, 0, 0]);
+    body.extend_from_slice(&[0; 6]);
+
+    sbf_block(4027 | 1 << 13, &body)
+}
@@ -0,0 +1,274 @@
+//! GNSS time stamps built from a block's TOW and WNc.
+//!
+//! Every block is stamped with the GPS week number `WNc`, counted
+//! continuously from the GPS epoch (1980-01-06 00:00:00 UTC), and the time of
+//! week `TOW` in milliseconds. [`GnssTime`] combines the two into one ordered
+//! value that stays correct across week boundaries.
+//!
+//! GPS time does not stop for leap seconds, so converting to UTC needs the
+//! current GPS-UTC offset: `ReceiverTime::delta_ls` or `GPSUtc::del_t_ls`, 18
+//! seconds since 2017.
+//!
+//! ```
+//! use libsbf::time::GnssTime;
+//! use core::time::Duration;
+//!
+//! let last = GnssTime::new(2360, 604_799_900).unwrap();
+//! let next = last + Duration::from_millis(200);
+//! assert_eq!((next.week(), next.tow_ms()), (2361, 100));
+//! assert_eq!(next.ms_since(last), 200);
+//! assert_eq!(next.to_unix_utc_ms(18), 1_743_897_582_100);
+//! ```
+
+use crate::Messages;
+
+use core::fmt;
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+use core::time::Duration;
+
+/// Milliseconds in a GPS week.
+pub const MS_PER_WEEK: u32 = 604_800_000;
+
+/// The GPS epoch, 1980-01-06 00:00:00 UTC, in milliseconds since the Unix
+/// epoch.
+const GPS_EPOCH_UNIX_MS: i64 = 315_964_800_000;
+
+/// A GPS time: week number and millisecond of the week. Ordered by time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GnssTime {
+    week: u16,
+    tow_ms: u32,
+}
+
+impl GnssTime {
+    /// The time `tow_ms` milliseconds into week `week`, or None if `tow_ms` is
+    /// a week or more.
+    pub fn new(week: u16, tow_ms: u32) -> Option<Self> {
+        (tow_ms < MS_PER_WEEK).then_some(Self { week, tow_ms })
+    }
+
+    /// The time `ms` milliseconds after the GPS epoch, or None past week
+    /// 65535.
+    pub fn from_ms(ms: u64) -> Option<Self> {
+        let week = u16::try_from(ms / u64::from(MS_PER_WEEK)).ok()?;
+        Some(Self {
+            week,
+            tow_ms: (ms % u64::from(MS_PER_WEEK)) as u32,
+        })
+    }
+
+    /// Continuous week number, WNc.
+    pub fn week(&self) -> u16 {
+        self.week
+    }
+
+    /// Time of week in milliseconds, TOW.
+    pub fn tow_ms(&self) -> u32 {
+        self.tow_ms
+    }
+
+    /// Time of week in seconds.
+    pub fn tow_seconds(&self) -> f64 {
+        f64::from(self.tow_ms) / 1000.0
+    }
+
+    /// Milliseconds since the GPS epoch.
+    pub fn as_ms(&self) -> u64 {
+        u64::from(self.week) * u64::from(MS_PER_WEEK) + u64::from(self.tow_ms)
+    }
+
+    /// Milliseconds from `earlier` to `self`, negative if `earlier` is
+    /// later.
+    pub fn ms_since(&self, earlier: GnssTime) -> i64 {
+        self.as_ms() as i64 - earlier.as_ms() as i64
+    }
+
+    /// Time from `earlier` to `self`, or None if `earlier` is later.
+    pub fn duration_since(&self, earlier: GnssTime) -> Option<Duration> {
+        let ms = self.as_ms().checked_sub(earlier.as_ms())?;
+        Some(Duration::from_millis(ms))
+    }
+
+    /// `self + duration`, truncated to whole milliseconds, or None past
+    /// week 65535.
+    pub fn checked_add(&self, duration: Duration) -> Option<Self> {
+        let ms = u64::try_from(duration.as_millis()).ok()?;
+        Self::from_ms(self.as_ms().checked_add(ms)?)
+    }
+
+    /// `self - duration`, truncated to whole milliseconds, or None before
+    /// the GPS epoch.
+    pub fn checked_sub(&self, duration: Duration) -> Option<Self> {
+        let ms = u64::try_from(duration.as_millis()).ok()?;
+        Self::from_ms(self.as_ms().checked_sub(ms)?)
+    }
+
+    /// UTC in milliseconds since the Unix epoch, given the GPS-UTC offset
+    /// `leap_seconds`.
+    pub fn to_unix_utc_ms(&self, leap_seconds: i8) -> i64 {
+        GPS_EPOCH_UNIX_MS + self.as_ms() as i64 - i64::from(leap_seconds) * 1000
+    }
+
+    /// The GPS time of `ms` milliseconds since the Unix epoch in UTC, given
+    /// the GPS-UTC offset `leap_seconds`, or None outside the weeks
+    /// [`GnssTime`] can hold.
+    pub fn from_unix_utc_ms(ms: i64, leap_seconds: i8) -> Option<Self> {
+        let gps_ms = ms
+            .checked_sub(GPS_EPOCH_UNIX_MS)?
+            .checked_add(i64::from(leap_seconds) * 1000)?;
+        Self::from_ms(u64::try_from(gps_ms).ok()?)
+    }
+
+    /// UTC as a `chrono` date and time, given the GPS-UTC offset
+    /// `leap_seconds`.
+    #[cfg(feature = "chrono")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+    pub fn to_chrono(&self, leap_seconds: i8) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::from_timestamp_millis(self.to_unix_utc_ms(leap_seconds))
+            .expect("GNSS times are within chrono's range")
+    }
+
+    /// The GPS time of a `chrono` UTC date and time, given the GPS-UTC
+    /// offset `leap_seconds`, or None outside the weeks [`GnssTime`] can
+    /// hold.
+    #[cfg(feature = "chrono")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+    pub fn from_chrono(utc: chrono::DateTime<chrono::Utc>, leap_seconds: i8) -> Option<Self> {
+        Self::from_unix_utc_ms(utc.timestamp_millis(), leap_seconds)
+    }
+
+    /// UTC as a `time` date and time, given the GPS-UTC offset
+    /// `leap_seconds`.
+    #[cfg(feature = "time")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "time")))]
+    pub fn to_offset_date_time(&self, leap_seconds: i8) -> ::time::OffsetDateTime {
+        let nanos = i128::from(self.to_unix_utc_ms(leap_seconds)) * 1_000_000;
+        ::time::OffsetDateTime::from_unix_timestamp_nanos(nanos)
+            .expect("GNSS times are within time's range")
+    }
+
+    /// The GPS time of a `time` date and time, given the GPS-UTC offset
+    /// `leap_seconds`, or None outside the weeks [`GnssTime`] can hold.
+    #[cfg(feature = "time")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "time")))]
+    pub fn from_offset_date_time(utc: ::time::OffsetDateTime, leap_seconds: i8) -> Option<Self> {
+        let ms = utc.unix_timestamp_nanos().div_euclid(1_000_000);
+        Self::from_unix_utc_ms(i64::try_from(ms).ok()?, leap_seconds)
+    }
+}
+
+impl Add<Duration> for GnssTime {
+    type Output = GnssTime;
+
+    /// Panics past week 65535, see [`GnssTime::checked_add`].
+    fn add(self, duration: Duration) -> GnssTime {
+        self.checked_add(duration)
+            .expect("overflow when adding duration to GnssTime")
+    }
+}
+
+impl AddAssign<Duration> for GnssTime {
+    fn add_assign(&mut self, duration: Duration) {
+        *self = *self + duration;
+    }
+}
+
+impl Sub<Duration> for GnssTime {
+    type Output = GnssTime;
+
+    /// Panics before the GPS epoch, see [`GnssTime::checked_sub`].
+    fn sub(self, duration: Duration) -> GnssTime {
+        self.checked_sub(duration)
+            .expect("overflow when subtracting duration from GnssTime")
+    }
+}
+
+impl SubAssign<Duration> for GnssTime {
+    fn sub_assign(&mut self, duration: Duration) {
+        *self = *self - duration;
+    }
+}
+
+/// Week and time of week in seconds, e.g. `2360:490403.000`.
+impl fmt::Display for GnssTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}.{:03}",
+            self.week,
+            self.tow_ms / 1000,
+            self.tow_ms % 1000
+        )
+    }
+}
+
+impl Messages {
+    /// The block's time stamp, or None if its TOW or WNc is the do-not-use
+    /// value.
+    pub fn time(&self) -> Option<GnssTime> {
+        GnssTime::new(self.wnc()?, self.tow()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GnssTime, MS_PER_WEEK};
+    use crate::parser::parse_datagram;
+    use crate::test_fixtures::VALID_QUALITY_IND;
+
+    use alloc::format;
+    use core::time::Duration;
+
+    #[test]
+    fn test_time_across_week_rollover() {
+        assert_eq!(GnssTime::new(1, MS_PER_WEEK), None);
+        let end = GnssTime::new(2360, MS_PER_WEEK - 1).unwrap();
+        let start = GnssTime::new(2361, 0).unwrap();
+        assert!(end < start);
+        assert_eq!(start.ms_since(end), 1);
+        assert_eq!(end.ms_since(start), -1);
+        assert_eq!(end.duration_since(start), None);
+        assert_eq!(end + Duration::from_millis(1), start);
+        assert_eq!(start - Duration::from_millis(1), end);
+        assert_eq!(
+            GnssTime::new(u16::MAX, MS_PER_WEEK - 1)
+                .unwrap()
+                .checked_add(Duration::from_millis(1)),
+            None
+        );
+        assert_eq!(
+            GnssTime::new(0, 0)
+                .unwrap()
+                .checked_sub(Duration::from_millis(1)),
+            None
+        );
+        assert_eq!(format!("{end}"), "2360:604799.999");
+    }
+
+    #[test]
+    fn test_time_to_utc() {
+        let msg = parse_datagram(VALID_QUALITY_IND).unwrap();
+        let time = msg.time().unwrap();
+        assert_eq!((time.week(), time.tow_ms()), (2360, 490403000));
+        // 2025-04-04 16:13:05 UTC.
+        let utc = 1_743_783_185_000;
+        assert_eq!(time.to_unix_utc_ms(18), utc);
+        assert_eq!(GnssTime::from_unix_utc_ms(utc, 18), Some(time));
+        assert_eq!(GnssTime::from_unix_utc_ms(0, 18), None);
+
+        #[cfg(feature = "chrono")]
+        {
+            use chrono::{Datelike, Timelike};
+            let dt = time.to_chrono(18);
+            assert_eq!((dt.year(), dt.month(), dt.day()), (2025, 4, 4));
+            assert_eq!((dt.hour(), dt.minute(), dt.second()), (16, 13, 5));
+            assert_eq!(GnssTime::from_chrono(dt, 18), Some(time));
+        }
+        #[cfg(feature = "time")]
+        {
+            let dt = time.to_offset_date_time(18);
+            assert_eq!(dt.unix_timestamp(), utc / 1000);
+            assert_eq!(GnssTime::from_offset_date_time(dt, 18), Some(time));
+        }
+    }
+}
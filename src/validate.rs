@@ -0,0 +1,243 @@
+//! Structural checks for strict parsing, see [`crate::parser::ParseOptions`].
+//!
+//! The decoders are lenient: they trust `N` and `SBLength` fields as long as
+//! the body is long enough, skip reserved fields and keep any trailing bytes
+//! as padding. These checks catch blocks that decode but disagree with
+//! themselves.
+
+use crate::Messages;
+
+/// A CRC-valid block whose structure is inconsistent, reported by strict
+/// parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Block number, bits 0-12 of the block ID.
+    pub block_id: u16,
+    /// The field that failed the check: `"length"` for the block length
+    /// implied by the counts and sub-block lengths, a sub-block length such
+    /// as `"sb_length"`, or a reserved field.
+    pub field: &'static str,
+    /// The value the rest of the block implies: the block length, the
+    /// minimum sub-block length, or zero for reserved fields.
+    pub expected: u64,
+    pub actual: u64,
+}
+
+/// Wire sizes of the sub-blocks the decoders model. `SBLength` may be larger,
+/// for fields added by newer firmware, but never smaller.
+const AUX_ANT_POSITION_SUB: usize = 52;
+const VECTOR_INFO: usize = 52;
+const DISK_DATA: usize = 14;
+const EXT_SENSOR_MEAS_SET: usize = 28;
+const MEAS_EXTRA_CHANNEL_SUB: usize = 16;
+const AGC_STATE: usize = 4;
+const RF_BAND: usize = 8;
+const SAT_INFO: usize = 8;
+const CHANNEL_SAT_INFO: usize = 12;
+const CHANNEL_STATE_INFO: usize = 8;
+const MEAS_EPOCH_CHANNEL_TYPE1: usize = 20;
+const MEAS_EPOCH_CHANNEL_TYPE2: usize = 12;
+
+/// Check a decoded block against its `body_len` byte body, everything after
+/// the 8 byte block header.
+pub(crate) fn validate(msg: &Messages, body_len: usize) -> Result<(), ValidationError> {
+    let check = Check {
+        block_id: msg.block_number(),
+        body_len,
+    };
+    match msg {
+        Messages::AuxAntPositions(m) => {
+            check.sb_length("sb_length", m.sb_length, AUX_ANT_POSITION_SUB)?;
+            check.length(8 + usize::from(m.n) * usize::from(m.sb_length))
+        }
+        Messages::BaseVectorCart(m) => {
+            check.sb_length("sb_length", m.sb_length, VECTOR_INFO)?;
+            check.length(8 + usize::from(m.n) * usize::from(m.sb_length))
+        }
+        Messages::BaseVectorGeod(m) => {
+            check.sb_length("sb_length", m.sb_length, VECTOR_INFO)?;
+            check.length(8 + usize::from(m.n) * usize::from(m.sb_length))
+        }
+        Messages::ChannelStatus(m) => {
+            check.reserved("reserved", &m.reserved)?;
+            check.sb_length("sb1_length", m.sb1_length, CHANNEL_SAT_INFO)?;
+            check.sb_length("sb2_length", m.sb2_length, CHANNEL_STATE_INFO)?;
+            let mut content = 12;
+            for sat in m.sat_info.iter() {
+                check.reserved("reserved1", &sat.reserved1)?;
+                check.reserved("reserved2", &[sat.reserved2])?;
+                for state in sat.state_info.iter() {
+                    check.reserved("reserved", &[state.reserved])?;
+                }
+                content +=
+                    usize::from(m.sb1_length) + sat.state_info.len() * usize::from(m.sb2_length);
+            }
+            check.length(content)
+        }
+        Messages::DiskStatus(m) => {
+            check.reserved("reserved", &m.reserved)?;
+            check.sb_length("sb_length", m.sb_length, DISK_DATA)?;
+            check.length(12 + usize::from(m.n) * usize::from(m.sb_length))
+        }
+        Messages::ExtSensorMeas(m) => {
+            check.sb_length("sb_length", m.sb_length, EXT_SENSOR_MEAS_SET)?;
+            for set in m.ext_sensor_meas_set.iter() {
+                if let Some(info) = &set.info {
+                    check.reserved("reserved", &info._reserved)?;
+                }
+                if let Some(flag) = &set.zero_vel_flag {
+                    check.reserved("reserved", &flag._reserved)?;
+                }
+            }
+            check.length(8 + usize::from(m.n) * usize::from(m.sb_length))
+        }
+        Messages::MeasEpoch(m) => {
+            check.reserved("reserved", &[m.rev1])?;
+            check.sb_length("sb1_length", m.sb1_length, MEAS_EPOCH_CHANNEL_TYPE1)?;
+            check.sb_length("sb2_length", m.sb2_length, MEAS_EPOCH_CHANNEL_TYPE2)?;
+            let content = m.channel_type1.iter().fold(12, |len, ch| {
+                len + usize::from(m.sb1_length) + ch.channel_type2.len() * usize::from(m.sb2_length)
+            });
+            check.length(content)
+        }
+        Messages::MeasExtra(m) => {
+            check.sb_length("sb_length", m.sb_length, MEAS_EXTRA_CHANNEL_SUB)?;
+            check.length(12 + usize::from(m.n) * usize::from(m.sb_length))
+        }
+        Messages::QualityInd(m) => {
+            check.reserved("reserved", &[m.reserved])?;
+            check.length(8 + 2 * usize::from(m.n))
+        }
+        Messages::ReceiverStatus(m) => {
+            check.sb_length("sb_length", m.sb_length, AGC_STATE)?;
+            check.length(24 + usize::from(m.n) * usize::from(m.sb_length))
+        }
+        Messages::RFStatus(m) => {
+            check.reserved("reserved", &m._reserved)?;
+            check.sb_length("sb_length", m.sb_length, RF_BAND)?;
+            check.length(12 + usize::from(m.n) * usize::from(m.sb_length))
+        }
+        Messages::SatVisibility(m) => {
+            check.sb_length("sb_length", m.sb_length, SAT_INFO)?;
+            check.length(8 + usize::from(m.n) * usize::from(m.sb_length))
+        }
+        Messages::Comment(m) => check.length(8 + usize::from(m.comment_ln)),
+        Messages::RxMessage(m) => {
+            check.reserved("reserved2", &m.reserved2)?;
+            check.length(16 + usize::from(m.string_ln))
+        }
+
+        // Fixed layouts: only the trailing padding can hide extra bytes.
+        Messages::BDSIon(m) => {
+            check.reserved("reserved", &[m.reserved])?;
+            check.padding(&m.padding)
+        }
+        Messages::GPSIon(m) => {
+            check.reserved("reserved", &[m.reserved])?;
+            check.padding(&m.padding)
+        }
+        Messages::GPSNav(m) => {
+            check.reserved("reserved", &[m.reserved])?;
+            check.reserved("reserved2", &[m.reserved2])?;
+            check.padding(&m.padding)
+        }
+        Messages::GPSUtc(m) => {
+            check.reserved("reserved", &[m.reserved])?;
+            check.padding(&m.padding)
+        }
+        Messages::NavCart(m) => {
+            check.reserved("reserved", &[m._reserved])?;
+            check.padding(&m.padding)
+        }
+        Messages::PosCart(m) => {
+            check.reserved("reserved", &[m._reserved])?;
+            check.padding(&m.padding)
+        }
+        Messages::VelSensorSetup(m) => {
+            check.reserved("reserved", &[m.reserved])?;
+            check.padding(&m.padding)
+        }
+        Messages::EndOfAtt(m) => check.padding(&m.padding),
+        Messages::EndOfMeas(m) => check.padding(&m.padding),
+        Messages::EndOfPVT(m) => check.padding(&m.padding),
+        Messages::ExtEvent(m) => check.padding(&m.padding),
+        Messages::GALGstGps(m) => check.padding(&m.padding),
+        Messages::GALIon(m) => check.padding(&m.padding),
+        Messages::GALNav(m) => check.padding(&m.padding),
+        Messages::GALUtc(m) => check.padding(&m.padding),
+        Messages::GPSCNav(m) => check.padding(&m.padding),
+        Messages::PVTCartesian(m) => check.padding(&m.padding),
+        Messages::PVTGeodetic(m) => check.padding(&m.padding),
+
+        // Reserved fields only.
+        Messages::AttCovEuler(m) => check.reserved("reserved", &[m.reserved]),
+        Messages::AttEuler(m) => check.reserved("reserved", &m._reserved.to_le_bytes()),
+        Messages::Commands(m) => check.reserved("reserved", &m.reserved),
+        Messages::DOP(m) => check.reserved("reserved", &[m.reserved]),
+        Messages::ExtEventINSNavCart(m) => check.reserved("reserved", &[m._reserved]),
+        Messages::ExtEventINSNavGeod(m) => check.reserved("reserved", &[m._reserved]),
+        Messages::GEONav(m) => check.reserved("reserved", &[m.reserved]),
+        Messages::ImuSetup(m) => check.reserved("reserved", &[m._reserved]),
+        Messages::INSNavCart(m) => check.reserved("reserved", &[m._reserved]),
+        Messages::INSNavGeod(m) => check.reserved("reserved", &[m._reserved]),
+        Messages::ReceiverSetup(m) => {
+            check.reserved("reserved", &m.reserved)?;
+            check.reserved("reserved1", m.reserved1.as_ref().map_or(&[], |r| &r[..]))
+        }
+        _ => Ok(()),
+    }
+}
+
+struct Check {
+    block_id: u16,
+    body_len: usize,
+}
+
+impl Check {
+    fn error(&self, field: &'static str, expected: usize, actual: usize) -> ValidationError {
+        ValidationError {
+            block_id: self.block_id,
+            field,
+            expected: expected as u64,
+            actual: actual as u64,
+        }
+    }
+
+    /// The block must be exactly `content` body bytes, padded to a multiple
+    /// of four with the header.
+    fn length(&self, content: usize) -> Result<(), ValidationError> {
+        let expected = (8 + content).next_multiple_of(4);
+        let actual = 8 + self.body_len;
+        if expected == actual {
+            Ok(())
+        } else {
+            Err(self.error("length", expected, actual))
+        }
+    }
+
+    /// Trailing padding may only align the block to four bytes.
+    fn padding(&self, padding: &[u8]) -> Result<(), ValidationError> {
+        self.length(self.body_len - padding.len())
+    }
+
+    fn sb_length(
+        &self,
+        field: &'static str,
+        sb_length: u8,
+        min: usize,
+    ) -> Result<(), ValidationError> {
+        if usize::from(sb_length) >= min {
+            Ok(())
+        } else {
+            Err(self.error(field, min, usize::from(sb_length)))
+        }
+    }
+
+    /// Reserved bytes must be zero; reports the first one that is not.
+    fn reserved(&self, field: &'static str, bytes: &[u8]) -> Result<(), ValidationError> {
+        match bytes.iter().find(|b| **b != 0) {
+            None => Ok(()),
+            Some(b) => Err(self.error(field, 0, usize::from(*b))),
+        }
+    }
+}
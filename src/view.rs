@@ -0,0 +1,593 @@
+//! Borrowed views of SBF blocks that read fields in place, without decoding
+//! the whole block or allocating.
+//!
+//! [`parse_ref`] checks a block's framing and CRC like [`parse_datagram`]
+//! and returns a [`MessageRef`]. Blocks with a dedicated view, such as
+//! [`MeasEpochRef`], expose their sub-blocks as iterators over the input;
+//! any block can still be fully decoded with [`MessageRef::decode`].
+//!
+//! [`parse_datagram`]: crate::parse_datagram
+
+use crate::parser::{check_frame, decode_body, DatagramError, ParseError};
+use crate::{binrw_util, BlockId, ExtSensorMeasSet, Id, Messages};
+
+use binrw::io::Cursor;
+use binrw::BinRead;
+
+/// Offset of the block body within a frame: sync, CRC, ID and length.
+const BODY_OFFSET: usize = 8;
+
+/// A CRC-checked block borrowed from the input, as returned by [`parse_ref`].
+#[derive(Clone, Copy, Debug)]
+pub enum MessageRef<'a> {
+    MeasEpoch(MeasEpochRef<'a>),
+    ExtSensorMeas(ExtSensorMeasRef<'a>),
+    /// A block without a dedicated view.
+    Other(BlockRef<'a>),
+}
+
+impl<'a> MessageRef<'a> {
+    /// The underlying block, for its ID, time stamp and bytes.
+    pub fn block(&self) -> BlockRef<'a> {
+        match self {
+            MessageRef::MeasEpoch(m) => m.block,
+            MessageRef::ExtSensorMeas(m) => m.block,
+            MessageRef::Other(block) => *block,
+        }
+    }
+
+    /// Fully decode the block into an owned message.
+    pub fn decode(&self) -> Result<Messages, DatagramError> {
+        self.block().decode()
+    }
+}
+
+/// Parse the block at the start of `datagram` into a borrowed view. The
+/// framing and CRC are checked as in [`crate::parse_datagram`], but the body
+/// is only read when its accessors are called. Unlike a UDP datagram, the
+/// block may use the full 16-bit SBF length.
+pub fn parse_ref(datagram: &[u8]) -> Result<MessageRef<'_>, DatagramError> {
+    if datagram.len() >= 2 && &datagram[..2] != b"$@" {
+        return Err(DatagramError::NoSync);
+    }
+    let (id, frame) = check_frame(datagram, usize::from(u16::MAX)).map_err(|e| match e {
+        ParseError::IncompleteData => DatagramError::Incomplete,
+        ParseError::InvalidCRC { .. } => DatagramError::InvalidCrc,
+        _ => DatagramError::InvalidHeader,
+    })?;
+    let block = BlockRef {
+        frame,
+        id: id.bytes,
+    };
+    // Every block starts with its TOW and WNc.
+    if block.body().len() < 6 {
+        return Err(DatagramError::InvalidPayload);
+    }
+
+    Ok(match id.message_type() {
+        Some(BlockId::MeasEpoch) => MessageRef::MeasEpoch(MeasEpochRef::new(block)?),
+        Some(BlockId::ExtSensorMeas) => MessageRef::ExtSensorMeas(ExtSensorMeasRef::new(block)?),
+        _ => MessageRef::Other(block),
+    })
+}
+
+/// A CRC-checked block: its frame from the `$@` sync through the padding.
+#[derive(Clone, Copy, Debug)]
+pub struct BlockRef<'a> {
+    frame: &'a [u8],
+    id: u16,
+}
+
+impl<'a> BlockRef<'a> {
+    /// Block number, bits 0-12 of the block ID.
+    pub fn block_number(&self) -> u16 {
+        self.id & 0x1FFF
+    }
+
+    /// Block revision number, bits 13-15 of the block ID.
+    pub fn revision(&self) -> u8 {
+        (self.id >> 13) as u8
+    }
+
+    pub fn tow(&self) -> Option<u32> {
+        binrw_util::map_u4(u32_at(self.body(), 0))
+    }
+
+    pub fn wnc(&self) -> Option<u16> {
+        binrw_util::map_u2(u16_at(self.body(), 4))
+    }
+
+    /// The complete framed block.
+    pub fn frame(&self) -> &'a [u8] {
+        self.frame
+    }
+
+    /// The block body after the header, starting with the time stamp.
+    pub fn body(&self) -> &'a [u8] {
+        &self.frame[BODY_OFFSET..]
+    }
+
+    /// Fully decode the block into an owned message.
+    pub fn decode(&self) -> Result<Messages, DatagramError> {
+        decode_body(&Id { bytes: self.id }, self.body()).map_err(|_| DatagramError::InvalidPayload)
+    }
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+/// Check that `body` holds `count` sub-blocks of `sb_len` bytes after a
+/// `header_len` byte header, each at least `min_len` bytes.
+fn check_sub_blocks(
+    body: &[u8],
+    header_len: usize,
+    count: usize,
+    sb_len: usize,
+    min_len: usize,
+) -> Result<(), DatagramError> {
+    if sb_len < min_len || body.len() < header_len + count * sb_len {
+        return Err(DatagramError::InvalidPayload);
+    }
+    Ok(())
+}
+
+/// Borrowed view of a MeasEpoch block 4027.
+#[derive(Clone, Copy, Debug)]
+pub struct MeasEpochRef<'a> {
+    block: BlockRef<'a>,
+}
+
+impl<'a> MeasEpochRef<'a> {
+    const HEADER_LEN: usize = 12;
+    const TYPE1_LEN: usize = 20;
+    const TYPE2_LEN: usize = 12;
+
+    // Walk the sub-blocks once so the iterators can index without checks.
+    fn new(block: BlockRef<'a>) -> Result<Self, DatagramError> {
+        let body = block.body();
+        if body.len() < Self::HEADER_LEN {
+            return Err(DatagramError::InvalidPayload);
+        }
+        let view = Self { block };
+        let (sb1_len, sb2_len) = (
+            usize::from(view.sb1_length()),
+            usize::from(view.sb2_length()),
+        );
+        if sb1_len < Self::TYPE1_LEN || sb2_len < Self::TYPE2_LEN {
+            return Err(DatagramError::InvalidPayload);
+        }
+
+        let mut at = Self::HEADER_LEN;
+        for _ in 0..view.n1() {
+            if body.len() < at + sb1_len {
+                return Err(DatagramError::InvalidPayload);
+            }
+            let n2 = usize::from(body[at + 19]);
+            at += sb1_len + n2 * sb2_len;
+        }
+        if body.len() < at {
+            return Err(DatagramError::InvalidPayload);
+        }
+        Ok(view)
+    }
+
+    pub fn block(&self) -> BlockRef<'a> {
+        self.block
+    }
+
+    pub fn tow(&self) -> Option<u32> {
+        self.block.tow()
+    }
+
+    pub fn wnc(&self) -> Option<u16> {
+        self.block.wnc()
+    }
+
+    pub fn n1(&self) -> u8 {
+        self.block.body()[6]
+    }
+
+    pub fn sb1_length(&self) -> u8 {
+        self.block.body()[7]
+    }
+
+    pub fn sb2_length(&self) -> u8 {
+        self.block.body()[8]
+    }
+
+    pub fn common_flags(&self) -> u8 {
+        self.block.body()[9]
+    }
+
+    pub fn cum_clk_jumps(&self) -> u8 {
+        self.block.body()[10]
+    }
+
+    /// The Type1 sub-blocks, one per satellite, each with its Type2
+    /// sub-blocks.
+    pub fn channels(&self) -> MeasEpochChannels<'a> {
+        MeasEpochChannels {
+            rest: &self.block.body()[Self::HEADER_LEN..],
+            remaining: self.n1(),
+            sb1_len: usize::from(self.sb1_length()),
+            sb2_len: usize::from(self.sb2_length()),
+        }
+    }
+}
+
+/// Iterator over the channels of a [`MeasEpochRef`].
+#[derive(Clone, Debug)]
+pub struct MeasEpochChannels<'a> {
+    rest: &'a [u8],
+    remaining: u8,
+    sb1_len: usize,
+    sb2_len: usize,
+}
+
+impl<'a> Iterator for MeasEpochChannels<'a> {
+    type Item = MeasEpochChannelRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let channel = MeasEpochChannelRef {
+            bytes: self.rest,
+            sb1_len: self.sb1_len,
+            sb2_len: self.sb2_len,
+        };
+        let len = self.sb1_len + usize::from(channel.n2()) * self.sb2_len;
+        self.rest = &self.rest[len..];
+        Some(channel)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = usize::from(self.remaining);
+        (n, Some(n))
+    }
+}
+
+impl ExactSizeIterator for MeasEpochChannels<'_> {}
+
+/// Borrowed MeasEpochChannelType1 sub-block; see
+/// [`crate::MeasEpochChannelType1`] for the decoded form.
+#[derive(Clone, Copy, Debug)]
+pub struct MeasEpochChannelRef<'a> {
+    /// Starts at this sub-block and runs to the end of the body.
+    bytes: &'a [u8],
+    sb1_len: usize,
+    sb2_len: usize,
+}
+
+impl<'a> MeasEpochChannelRef<'a> {
+    pub fn rx_channel(&self) -> u8 {
+        self.bytes[0]
+    }
+
+    pub fn type_field(&self) -> u8 {
+        self.bytes[1]
+    }
+
+    pub fn svid(&self) -> u8 {
+        self.bytes[2]
+    }
+
+    pub fn misc(&self) -> u8 {
+        self.bytes[3]
+    }
+
+    pub fn code_lsb(&self) -> u32 {
+        u32_at(self.bytes, 4)
+    }
+
+    pub fn doppler(&self) -> Option<i32> {
+        match u32_at(self.bytes, 8) as i32 {
+            i32::MIN => None,
+            x => Some(x),
+        }
+    }
+
+    pub fn carrier_lsb(&self) -> u16 {
+        u16_at(self.bytes, 12)
+    }
+
+    pub fn carrier_msb(&self) -> i8 {
+        self.bytes[14] as i8
+    }
+
+    pub fn cn0(&self) -> Option<u8> {
+        binrw_util::map_u1(self.bytes[15])
+    }
+
+    pub fn lock_time(&self) -> Option<u16> {
+        binrw_util::map_u2(u16_at(self.bytes, 16))
+    }
+
+    pub fn obs_info(&self) -> u8 {
+        self.bytes[18]
+    }
+
+    pub fn n2(&self) -> u8 {
+        self.bytes[19]
+    }
+
+    /// The Type2 sub-blocks with this satellite's other signals.
+    pub fn signals(&self) -> MeasEpochSignals<'a> {
+        MeasEpochSignals {
+            rest: &self.bytes[self.sb1_len..],
+            remaining: self.n2(),
+            sb2_len: self.sb2_len,
+        }
+    }
+}
+
+/// Iterator over the Type2 sub-blocks of a [`MeasEpochChannelRef`].
+#[derive(Clone, Debug)]
+pub struct MeasEpochSignals<'a> {
+    rest: &'a [u8],
+    remaining: u8,
+    sb2_len: usize,
+}
+
+impl<'a> Iterator for MeasEpochSignals<'a> {
+    type Item = MeasEpochSignalRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let (bytes, rest) = self.rest.split_at(self.sb2_len);
+        self.rest = rest;
+        Some(MeasEpochSignalRef { bytes })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = usize::from(self.remaining);
+        (n, Some(n))
+    }
+}
+
+impl ExactSizeIterator for MeasEpochSignals<'_> {}
+
+/// Borrowed MeasEpochChannelType2 sub-block; see
+/// [`crate::MeasEpochChannelType2`] for the decoded form.
+#[derive(Clone, Copy, Debug)]
+pub struct MeasEpochSignalRef<'a> {
+    bytes: &'a [u8],
+}
+
+impl MeasEpochSignalRef<'_> {
+    pub fn type_field(&self) -> u8 {
+        self.bytes[0]
+    }
+
+    pub fn lock_time(&self) -> Option<u8> {
+        binrw_util::map_u1(self.bytes[1])
+    }
+
+    pub fn cn0(&self) -> Option<u8> {
+        binrw_util::map_u1(self.bytes[2])
+    }
+
+    pub fn offsets_msb(&self) -> u8 {
+        self.bytes[3]
+    }
+
+    pub fn carrier_msb(&self) -> i8 {
+        self.bytes[4] as i8
+    }
+
+    pub fn obs_info(&self) -> u8 {
+        self.bytes[5]
+    }
+
+    pub fn code_offset_lsb(&self) -> u16 {
+        u16_at(self.bytes, 6)
+    }
+
+    pub fn carrier_lsb(&self) -> u16 {
+        u16_at(self.bytes, 8)
+    }
+
+    pub fn doppler_offset_lsb(&self) -> u16 {
+        u16_at(self.bytes, 10)
+    }
+}
+
+/// Borrowed view of an ExtSensorMeas block 4050.
+#[derive(Clone, Copy, Debug)]
+pub struct ExtSensorMeasRef<'a> {
+    block: BlockRef<'a>,
+}
+
+impl<'a> ExtSensorMeasRef<'a> {
+    const HEADER_LEN: usize = 8;
+    const SET_LEN: usize = 4;
+
+    fn new(block: BlockRef<'a>) -> Result<Self, DatagramError> {
+        let body = block.body();
+        if body.len() < Self::HEADER_LEN {
+            return Err(DatagramError::InvalidPayload);
+        }
+        let view = Self { block };
+        check_sub_blocks(
+            body,
+            Self::HEADER_LEN,
+            usize::from(view.n()),
+            usize::from(view.sb_length()),
+            Self::SET_LEN,
+        )?;
+        Ok(view)
+    }
+
+    pub fn block(&self) -> BlockRef<'a> {
+        self.block
+    }
+
+    pub fn tow(&self) -> Option<u32> {
+        self.block.tow()
+    }
+
+    pub fn wnc(&self) -> Option<u16> {
+        self.block.wnc()
+    }
+
+    pub fn n(&self) -> u8 {
+        self.block.body()[6]
+    }
+
+    pub fn sb_length(&self) -> u8 {
+        self.block.body()[7]
+    }
+
+    /// The measurement set sub-blocks.
+    pub fn sets(&self) -> impl ExactSizeIterator<Item = ExtSensorMeasSetRef<'a>> + Clone + 'a {
+        self.block.body()[Self::HEADER_LEN..]
+            .chunks_exact(usize::from(self.sb_length()))
+            .take(usize::from(self.n()))
+            .map(|bytes| ExtSensorMeasSetRef { bytes })
+    }
+}
+
+/// Borrowed ExtSensorMeasSet sub-block.
+#[derive(Clone, Copy, Debug)]
+pub struct ExtSensorMeasSetRef<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ExtSensorMeasSetRef<'a> {
+    pub fn source(&self) -> u8 {
+        self.bytes[0]
+    }
+
+    pub fn sensor_model(&self) -> u8 {
+        self.bytes[1]
+    }
+
+    /// Measurement type, see [`crate::ExtSensorMeasSetType`].
+    pub fn type_(&self) -> u8 {
+        self.bytes[2]
+    }
+
+    pub fn obs_info(&self) -> u8 {
+        self.bytes[3]
+    }
+
+    /// The type-specific measurement bytes after the common fields.
+    pub fn data(&self) -> &'a [u8] {
+        &self.bytes[4..]
+    }
+
+    /// Decode this set alone into its owned form.
+    pub fn decode(&self) -> Result<ExtSensorMeasSet, DatagramError> {
+        ExtSensorMeasSet::read_le(&mut Cursor::new(self.bytes))
+            .map_err(|_| DatagramError::InvalidPayload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_ref, MessageRef};
+    use crate::parser::{parse_datagram, DatagramError};
+    use crate::test_fixtures::{long_block, meas_epoch_block, VALID_QUALITY_IND};
+    use crate::Messages;
+
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_parse_ref_other_block() {
+        let msg = parse_ref(VALID_QUALITY_IND).unwrap();
+        let MessageRef::Other(block) = msg else {
+            panic!("expected a generic view, got {msg:?}");
+        };
+        assert_eq!(block.block_number(), 4082);
+        assert_eq!(block.revision(), 0);
+        assert_eq!(block.tow(), Some(490403000));
+        assert_eq!(block.wnc(), Some(2360));
+        assert_eq!(block.frame(), &VALID_QUALITY_IND[..]);
+        assert!(matches!(msg.decode(), Ok(Messages::QualityInd(_))));
+    }
+
+    #[test]
+    fn test_parse_ref_checks_crc() {
+        let mut bad_crc = *VALID_QUALITY_IND;
+        bad_crc[2] ^= 0xFF;
+        assert!(matches!(
+            parse_ref(&bad_crc),
+            Err(DatagramError::InvalidCrc)
+        ));
+        assert_eq!(
+            parse_datagram(&bad_crc).unwrap_err(),
+            DatagramError::InvalidCrc
+        );
+    }
+
+    #[test]
+    fn test_parse_ref_meas_epoch() {
+        let block = meas_epoch_block();
+        let msg = parse_ref(&block).unwrap();
+        let MessageRef::MeasEpoch(epoch) = msg else {
+            panic!("expected a MeasEpoch view, got {msg:?}");
+        };
+        assert_eq!(epoch.block().revision(), 1);
+        assert_eq!(epoch.tow(), Some(490403000));
+        assert_eq!(epoch.wnc(), Some(2360));
+        assert_eq!(epoch.n1(), 2);
+
+        let channels: Vec<_> = epoch.channels().collect();
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels[0].svid(), 5);
+        assert_eq!(channels[0].code_lsb(), 0x2345_6789);
+        assert_eq!(channels[0].doppler(), Some(-12_345_678));
+        let signals: Vec<_> = channels[0].signals().collect();
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].type_field(), 4);
+        assert_eq!(signals[0].cn0(), Some(120));
+        assert_eq!(signals[0].code_offset_lsb(), 0xFFFF);
+        assert_eq!(signals[0].doppler_offset_lsb(), 100);
+
+        assert_eq!(channels[1].svid(), 38);
+        assert_eq!(channels[1].obs_info(), 9 << 3);
+        assert_eq!(channels[1].signals().len(), 1);
+        assert_eq!(channels[1].signals().next().unwrap().cn0(), Some(104));
+
+        let Ok(Messages::MeasEpoch(decoded)) = msg.decode() else {
+            panic!("expected MeasEpoch");
+        };
+        assert_eq!(decoded.channel_type1.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_ref_checks_sub_block_bounds() {
+        // One more channel than the body holds.
+        let mut block = meas_epoch_block();
+        block[14] = 3;
+        let crc = crc16::State::<crc16::XMODEM>::calculate(&block[4..]);
+        block[2..4].copy_from_slice(&crc.to_le_bytes());
+        assert!(matches!(
+            parse_ref(&block),
+            Err(DatagramError::InvalidPayload)
+        ));
+    }
+
+    #[test]
+    fn test_parse_ref_long_block() {
+        let block = long_block();
+        let msg = parse_ref(&block).unwrap();
+        assert_eq!(msg.block().block_number(), 4999);
+        assert_eq!(msg.block().frame().len(), block.len());
+        assert_eq!(
+            parse_datagram(&block).unwrap_err(),
+            DatagramError::ExceedsMaxUdpPayload(65532)
+        );
+    }
+}